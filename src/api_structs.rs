@@ -7,7 +7,8 @@ pub struct GradleVersion {
     // pub buildTime: String,
     // pub commitId: String,
     // pub current: bool,
-    // pub snapshot: bool,
+    #[serde(default)]
+    pub snapshot: bool,
     // pub nightly: bool,
     // pub releaseNightly: bool,
     // pub activeRc: bool,
@@ -15,8 +16,10 @@ pub struct GradleVersion {
     // pub milestoneFor: String,
     // pub broken: bool,
     pub downloadUrl: String,
-    // pub checksumUrl: String,
-    // pub wrapperChecksumUrl: String,
+    pub checksumUrl: String,
+    /// Missing for versions too old to have a published wrapper checksum.
+    #[serde(default)]
+    pub wrapperChecksumUrl: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -26,11 +29,42 @@ pub struct LoomVersion {
 
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct MinecraftVersion {
+pub struct GameVersion {
     pub version: String,
     pub stable: bool,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VersionManifest {
+    // pub latest: VersionManifestLatest,
+    pub versions: Box<[VersionManifestEntry]>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct VersionManifestEntry {
+    pub id: String,
+    // pub r#type: String,
+    pub url: String,
+    // pub time: String,
+    pub releaseTime: String,
+    // pub sha1: String,
+    // pub complianceLevel: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct VersionDetails {
+    pub javaVersion: Option<VersionJavaVersion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct VersionJavaVersion {
+    // pub component: String,
+    pub majorVersion: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct YarnMappingsVersion {
@@ -1,16 +1,17 @@
-use std::{io::{Read, Write}, path::PathBuf};
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
 
-use crate::{api_structs::{FabricLoaderVersion, GradleVersion, LoomVersion, MinecraftVersion, ProjectVersion, YarnMappingsVersion}, common::*, semantic_version::{SemanticVersion, SemanticVersionRange, simplify_range_set}};
+use crate::{api_structs::{FabricLoaderVersion, GameVersion, GradleVersion, LoomVersion, ProjectFile, ProjectVersion, YarnMappingsVersion}, common::*, config::{Config, MappingMode}, journal::{Journal, Operation, Snapshot}, lockfile::{LOCKFILE, LockedDependency, Lockfile}, minecraft_version::MinecraftVersion, properties::Properties, semantic_version::{SemanticVersion, SemanticVersionRange, VersionRangeSet}, state::state_changes, workspace::{SweepResults, TestOutcome}};
 
 
+/// Java versions needed by Minecraft versions, used when they couldn't be fetched from Mojang's version manifest.
 pub const JAVA_VERSION_TABLE: [(SemanticVersion, u32); 4] = [
-    (SemanticVersion { major: 0, minor: 0, patch: 0, release: None, build: None }, 8),
-    (SemanticVersion { major: 1, minor: 17, patch: 0, release: None, build: None }, 16),
-    (SemanticVersion { major: 1, minor: 18, patch: 0, release: None, build: None }, 17),
-    (SemanticVersion { major: 1, minor: 20, patch: 5, release: None, build: None }, 21),
+    (SemanticVersion::new(0, 0, 0), 8),
+    (SemanticVersion::new(1, 17, 0), 16),
+    (SemanticVersion::new(1, 18, 0), 17),
+    (SemanticVersion::new(1, 20, 5), 21),
 ];
 
-fn get_java_version(mc_version: &SemanticVersion) -> u32 {
+pub fn get_java_version(mc_version: &SemanticVersion) -> u32 {
     JAVA_VERSION_TABLE.iter().rev().find_map(|(mc_version_start, java_version)| {
         if *mc_version >= *mc_version_start {
             Some(*java_version)
@@ -18,28 +19,103 @@ fn get_java_version(mc_version: &SemanticVersion) -> u32 {
     }).unwrap_or(8)
 }
 
-pub const GRADLE: &str = "./gradlew.bat";
-const GRADLE_PROPERTIES: &str = "gradle.properties";
-pub const LOCAL_MAVEN: &str = "local_maven";
+/// Pick the Minecraft version closest to `version` that a dependency supports.
+/// Versions from the same minor line are preferred, the nearest patch first and the earlier one on a tie, then the closest earlier version.
+pub fn closest_game_version<'a>(version: &MinecraftVersion, supported: &'a [MinecraftVersion]) -> Option<&'a MinecraftVersion> {
+    let semver = &version.semver;
+    let same_minor = supported.iter()
+        .filter(|v| v.semver.major == semver.major && v.semver.minor == semver.minor)
+        .min_by_key(|v| (v.semver.patch.abs_diff(semver.patch), v.semver.patch > semver.patch));
+    same_minor.or_else(|| supported.iter().filter(|v| *v < version).max())
+}
+
+/// Every version listed in a maven-metadata.xml, in the order listed.
+pub fn maven_metadata_versions(metadata: &str) -> Vec<String> {
+    metadata.split("<version>").skip(1)
+        .filter_map(|s| s.split_once("</version>").map(|(s, _)| s.trim().to_owned()))
+        .collect()
+}
+
+/// Fabric API builds for a Minecraft version, which end in `+<version>`, newest first.
+pub fn fabric_api_builds_for<'a>(builds: &'a [String], version: &MinecraftVersion) -> Vec<&'a str> {
+    let suffix = format!("+{}", version);
+    let mut builds = builds.iter()
+        .filter(|s| s.ends_with(&suffix))
+        .filter_map(|s| Some((s.parse::<SemanticVersion>().ok()?, s.as_str())))
+        .collect::<Vec<_>>();
+    builds.sort_by(|(a, _), (b, _)| b.cmp(a));
+    builds.into_iter().map(|(_, build)| build).collect()
+}
+
+/// Lowest version of a dependency the mod is known to work with: the lower of the version resolved for the current
+/// Minecraft version and the one resolved for the oldest confirmed Minecraft version. `None` if the dependency
+/// doesn't use semantic versions, which fabric.mod.json can't compare.
+pub fn minimum_dependency_version(resolved: &str, oldest: Option<&str>) -> Option<SemanticVersion> {
+    let minimum = resolved.parse::<SemanticVersion>().ok()?;
+    Some(match oldest.and_then(|oldest| oldest.parse::<SemanticVersion>().ok()) {
+        Some(oldest) if oldest < minimum => oldest,
+        _ => minimum,
+    })
+}
+
+pub const GRADLE_PROPERTIES: &str = "gradle.properties";
+pub const MAX_BACKUPS: usize = 50;
 
 
 pub struct App {
     pub cwd: PathBuf,
     pub http_client: reqwest::blocking::Client,
-    pub mc_versions: Box<[(SemanticVersion, u32)]>,
+    /// Known Minecraft releases with their latest yarn build, newest first.
+    pub mc_versions: Box<[(MinecraftVersion, u32)]>,
+    /// Java version needed by each Minecraft version, by version id.
+    pub java_versions: BTreeMap<String, u32>,
+    /// Every yarn build of each Minecraft version, by version id, oldest first.
+    pub yarn_builds: BTreeMap<String, Vec<u32>>,
+    pub journal: Journal,
+    pub config: Config,
 }
 
 impl App {
-    pub fn new() -> Self {
-        let http_client = reqwest::blocking::Client::builder().user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"))).build().unwrap();
-        Self {
-            cwd: std::env::current_dir().expect("No current working directory access"),
+    pub fn new() -> Result<Self> {
+        let http_client = reqwest::blocking::Client::builder().user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"))).build()
+            .map_err(|e| format!("Could not set up the HTTP client: {}", e))?;
+        Ok(Self {
+            cwd: std::env::current_dir().map_err(|e| format!("Could not access the working directory: {}", e))?,
             http_client,
-            mc_versions: Box::new([(SemanticVersion::default(), 0); 0]),
-        }
+            mc_versions: Box::new([]),
+            java_versions: BTreeMap::new(),
+            yarn_builds: BTreeMap::new(),
+            journal: Journal::default(),
+            config: Config::default(),
+        })
+    }
+    
+    /// Read the ralli settings of the project in the working directory.
+    pub fn load_config(&mut self) -> Result<()> {
+        self.config = Config::load(&self.cwd)?;
+        Ok(())
     }
     
-    fn api_request<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+    pub fn run_gradle(&self, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> Result<()> {
+        self.run_gradle_status(args)?;
+        Ok(())
+    }
+    
+    /// Run gradle in the project folder, returning whether it succeeded.
+    pub fn run_gradle_status(&self, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> Result<bool> {
+        // A gradle path like `./gradlew.bat` is relative to the project, a bare command is looked up on the PATH
+        let gradle = std::path::Path::new(&self.config.paths.gradle);
+        let gradle = if gradle.components().count() > 1 { self.cwd.join(gradle) } else { gradle.to_path_buf() };
+        run_command_in(&self.cwd, gradle, args)
+    }
+    
+    /// Index of a Minecraft version in `mc_versions`.
+    pub fn version_index(&self, s: &str) -> Result<usize> {
+        let version = s.parse::<MinecraftVersion>().map_err(|_| format!("'{}' isn't a version!", s))?;
+        self.mc_versions.iter().position(|(v, _)| *v == version).ok_or(format!("Minecraft version {} not found.", version).into())
+    }
+    
+    pub fn api_request<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
         let response = self.http_client.get(url).send()?;
         if !response.status().is_success() {
             return Err(format!("{:?}", response.error_for_status()).into())
@@ -47,6 +123,14 @@ impl App {
         Ok(response.json::<T>()?)
     }
     
+    pub fn api_request_text(&self, url: &str) -> Result<String> {
+        let response = self.http_client.get(url).send()?;
+        if !response.status().is_success() {
+            return Err(format!("{:?}", response.error_for_status()).into())
+        }
+        Ok(response.text()?.trim().to_owned())
+    }
+    
     fn api_download_file(&self, url: &str, path: impl AsRef<std::path::Path>) -> Result<()> {
         let response = self.http_client.get(url).send()?;
         if !response.status().is_success() {
@@ -57,280 +141,629 @@ impl App {
         Ok(())
     }
     
-    fn read_properties(&self) -> Result<String> {
-        let mut file = std::fs::File::options().read(true).open(&self.cwd.join(GRADLE_PROPERTIES))?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Ok(contents)
+    fn api_file_exists(&self, url: &str) -> Result<bool> {
+        Ok(self.http_client.head(url).send()?.status().is_success())
+    }
+    
+    pub fn read_properties(&self) -> Result<Properties> {
+        Ok(Properties::parse(&std::fs::read_to_string(self.cwd.join(GRADLE_PROPERTIES))?))
     }
     
-    fn write_properties(&self, contents: &str) -> Result<()> {
-        let mut file = std::fs::File::options().write(true).create(true).truncate(true).open(&self.cwd.join(GRADLE_PROPERTIES))?;
-        file.write_all(contents.as_bytes())?;
+    pub fn write_properties(&self, properties: &Properties) -> Result<()> {
+        let path = self.cwd.join(GRADLE_PROPERTIES);
+        let contents = properties.to_string();
+        if std::fs::exists(&path)? {
+            let previous = std::fs::read_to_string(&path)?;
+            if previous == contents { return Ok(()) }
+            self.backup_properties(&previous)?;
+        }
+        write_atomic(path, contents)
+    }
+    
+    fn backup_properties(&self, contents: &str) -> Result<()> {
+        let backups_dir = self.cwd.join(&self.config.paths.backups);
+        std::fs::create_dir_all(&backups_dir)?;
+        // Writes within the same millisecond get a counter, which still sorts after the plain name
+        let name = format!("{}.{}", GRADLE_PROPERTIES, timestamp());
+        let mut path = backups_dir.join(&name);
+        for i in 1.. {
+            if !std::fs::exists(&path)? { break }
+            path = backups_dir.join(format!("{}.{:03}", name, i));
+        }
+        write_atomic(path, contents)?;
+        
+        let backups = self.list_backups()?;
+        for old_backup in backups.iter().skip(MAX_BACKUPS) {
+            std::fs::remove_file(old_backup)?;
+        }
         Ok(())
     }
     
-    fn find_property<'a>(&self, contents: &'a str, name: &str) -> Result<SubstringRef<'a>> {
-        let start_pattern = format!("\n{name}=");
-        (|| {
-            let (before, rest) = contents.split_at_checked(contents.find(&start_pattern)? + start_pattern.len())?;
-            let line = rest.split_once("\n").map(|(s, _)| s).unwrap_or(rest);
-            let substring = line.split_once("#").map(|(s, _)| s).unwrap_or(line).trim_end();
-            let after = rest.split_at_checked(substring.len())?.1;
-            Some(SubstringRef { before, substring, after })
-        })().ok_or(format!("No property '{name}' found in gradle properties.").into())
-    }
-    
-    fn parse_ranges_slice(&self, ranges_part: &SubstringRef) -> Result<Vec<SemanticVersionRange>> {
-        let mut ranges = vec![];
-        for range_string in ranges_part.substring.trim().trim_start_matches("[").trim_end_matches("]").split(",") {
-            if range_string.is_empty() { continue }
-            match range_string.trim().trim_matches('\"').parse() {
-                Ok(range) => ranges.push(range),
-                Err(e) => return Err(e),
-            }
+    /// Backups of gradle.properties, newest first.
+    pub fn list_backups(&self) -> Result<Vec<PathBuf>> {
+        let backups_dir = self.cwd.join(&self.config.paths.backups);
+        if !std::fs::exists(&backups_dir)? { return Ok(vec![]) }
+        let prefix = format!("{}.", GRADLE_PROPERTIES);
+        let mut backups = std::fs::read_dir(backups_dir)?.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix)))
+            .collect::<Vec<_>>();
+        backups.sort_by(|a, b| b.cmp(a));
+        Ok(backups)
+    }
+    
+    /// Replace gradle.properties with a backup. The current contents are backed up first, so a restore can itself be undone.
+    pub fn restore_backup(&self, backup: &std::path::Path) -> Result<()> {
+        let contents = std::fs::read_to_string(backup)?;
+        self.write_properties(&Properties::parse(&contents))?;
+        println!("Restored gradle properties from backup {}.", backup.file_name().unwrap_or_default().to_string_lossy());
+        Ok(())
+    }
+    
+    pub fn find_property(&self, properties: &Properties, name: &str) -> Result<String> {
+        properties.get(name).ok_or(format!("No property '{name}' found in gradle properties.").into())
+    }
+    
+    /// Read a comma separated list property, treating a missing property as an empty list.
+    pub fn find_list_property(&self, properties: &Properties, name: &str) -> Vec<String> {
+        match properties.get(name) {
+            Some(value) => value.split(',').map(|s| s.trim().to_owned()).filter(|s| !s.is_empty()).collect(),
+            None => vec![],
         }
-        Ok(ranges)
     }
     
-    fn parse_current_ranges(&self, contents: &str) -> Result<Vec<SemanticVersionRange>> {
-        self.parse_ranges_slice(&self.find_property(contents, "minecraft_compatible_range")?)
+    /// Index of the ralli marker comment, after which the ralli driven values and dependencies are listed.
+    pub fn find_ralli_marker(&self, properties: &Properties) -> Option<usize> {
+        let marker = self.config.paths.marker.to_lowercase();
+        properties.find_comment(|comment| comment.trim_start().to_lowercase().starts_with(&marker))
+    }
+    
+    /// Set a property, adding it after the ralli marker comment if it does not exist yet.
+    pub fn set_ralli_property(&self, properties: &mut Properties, name: &str, value: &str) {
+        match self.find_ralli_marker(properties) {
+            Some(line) => properties.set_or_insert_after(line, name, value),
+            None => properties.set(name, value),
+        }
     }
     
-    pub fn get_current_ranges(&self) -> Result<Vec<SemanticVersionRange>> {
-        let file_path = self.cwd.join(GRADLE_PROPERTIES);
-        let mut file = std::fs::File::options().read(true).open(&file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Ok(simplify_range_set(self.parse_current_ranges(&contents)?))
+    pub fn parse_current_ranges(&self, properties: &Properties) -> Result<VersionRangeSet> {
+        self.find_property(properties, &self.config.properties.compatible_range)?.parse()
     }
     
-    pub fn update_gradle(&self) -> Result<()> {
-        let version = self.api_request::<GradleVersion>("https://services.gradle.org/versions/current")?;
-        let new_url = version.downloadUrl.replace(":", "\\:");
+    /// Sorted and merged compatible ranges of the project.
+    pub fn get_current_ranges(&self) -> Result<VersionRangeSet> {
+        Ok(self.parse_current_ranges(&self.read_properties()?)?.simplified())
+    }
+    
+    /// Project files besides gradle.properties and the lockfile that recorded commands can write.
+    fn snapshot_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.cwd.join("build.gradle"), self.cwd.join(&self.config.paths.gradle_wrapper_properties)];
+        files.extend(self.find_fabric_mod_json());
+        files
+    }
+    
+    fn snapshot(&self) -> Result<Snapshot> {
+        let read_optional = |path: PathBuf| -> Result<Option<String>> {
+            Ok(if std::fs::exists(&path)? { Some(std::fs::read_to_string(path)?) } else { None })
+        };
+        let mods_dir = self.cwd.join(&self.config.paths.run_mods);
+        let mut mods = vec![];
+        if std::fs::exists(&mods_dir)? {
+            for entry in std::fs::read_dir(mods_dir)?.flatten() {
+                if entry.file_type().is_ok_and(|t| t.is_file()) && !entry.file_name().to_string_lossy().starts_with("_") {
+                    mods.push(entry.file_name());
+                }
+            }
+        }
+        mods.sort();
+        let mut files = vec![];
+        for path in self.snapshot_files() {
+            let contents = if std::fs::exists(&path)? { Some(std::fs::read(&path)?) } else { None };
+            files.push((path, contents));
+        }
+        Ok(Snapshot {
+            properties: read_optional(self.cwd.join(GRADLE_PROPERTIES))?,
+            lockfile: read_optional(self.cwd.join(LOCKFILE))?,
+            mods,
+            files,
+        })
+    }
+    
+    fn restore_snapshot(&self, snapshot: &Snapshot) -> Result<()> {
+        if let Some(properties) = &snapshot.properties {
+            self.write_properties(&Properties::parse(properties))?;
+        }
+        match &snapshot.lockfile {
+            Some(lockfile) => write_atomic(self.cwd.join(LOCKFILE), lockfile)?,
+            None => if std::fs::exists(self.cwd.join(LOCKFILE))? { std::fs::remove_file(self.cwd.join(LOCKFILE))? }
+        }
         
-        let file_path = self.cwd.join("gradle/wrapper/gradle-wrapper.properties");
-        let mut file = std::fs::File::options().read(true).open(&file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+        let mods_dir = self.cwd.join(&self.config.paths.run_mods);
+        std::fs::create_dir_all(&mods_dir)?;
+        clean_folder(&mods_dir)?;
+        for file_name in &snapshot.mods {
+            std::fs::copy(self.cwd.join(&self.config.paths.local_maven).join(file_name), mods_dir.join(file_name))
+                .map_err(|e| format!("Could not restore '{}' to {}: {}", file_name.to_string_lossy(), self.config.paths.run_mods, e))?;
+        }
         
-        let gradle_url_part = SubstringRef::find(&contents, "distributionUrl=", "\n").ok_or("Could not find location of active gradle source.")?;
-        if gradle_url_part.substring.trim() == new_url.trim() {
-            println!("Gradle version {} is up to date.", version.version);
-        } else {
-            let new_contents = gradle_url_part.replace(&new_url);
-            let mut file = std::fs::File::options().write(true).create(true).truncate(true).open(&file_path)?;
-            file.write_all(new_contents.as_bytes())?;
-            
-            println!("Updating gradle version to {}", version.version);
-            run_command(GRADLE, ["--version"])?;
+        for (path, contents) in &snapshot.files {
+            let current = if std::fs::exists(path)? { Some(std::fs::read(path)?) } else { None };
+            if current == *contents { continue }
+            match contents {
+                Some(contents) => write_atomic(path, contents)?,
+                None => std::fs::remove_file(path)?,
+            }
         }
         Ok(())
     }
     
-    pub fn update_static_info(&self) -> Result<()> {
-        let loom_version = self.api_request::<LoomVersion>("https://api.github.com/repos/FabricMC/fabric-loom/releases/latest")?.tag_name;
-        let loom_version_full = format!("{}-SNAPSHOT", loom_version);
-        let loader_version = self.api_request::<Box<[FabricLoaderVersion]>>("https://meta.fabricmc.net/v2/versions/loader")?.iter().filter(|v| v.stable).next().ok_or("No stable loader versions found.")?.version.clone();
-        
-        let contents = self.read_properties()?;
-        let mut changed = false;
-        
-        let loom_version_part = self.find_property(&contents, "loom_version")?;
-        let contents = if loom_version_part.substring.trim() == loom_version_full {
-            println!("Loom version {} is up to date.", loom_version);
-            contents
-        } else {
-            println!("Updating loom to version {}", loom_version);
-            changed = true;
-            loom_version_part.replace(&loom_version_full)
-        };
-        
-        let loader_version_part = self.find_property(&contents, "loader_version")?;
-        let contents = if loader_version_part.substring.trim() == loader_version {
-            println!("Loader version {} is up to date.", loader_version);
-            contents
-        } else {
-            println!("Updating loader to version {}", loader_version);
-            changed = true;
-            loader_version_part.replace(&loader_version)
-        };
-        
-        if changed {
-            self.write_properties(&contents)?;
+    /// Run a command that changes the project and record it in the journal so it can be undone.
+    pub fn record(&mut self, name: &str, operation: impl FnOnce(&Self) -> Result<()>) -> Result<()> {
+        let before = self.snapshot()?;
+        let result = operation(self);
+        let after = self.snapshot()?;
+        self.journal.record(Operation { name: name.to_owned(), before, after });
+        if result.is_ok() { self.save_current_state()?; }
+        result
+    }
+    
+    /// Save the state of the project after a command changed it, so later hand edits can be told apart.
+    pub fn save_current_state(&self) -> Result<()> {
+        // A project that isn't fully set up yet has no state to save
+        if let Ok(state) = self.read_properties().and_then(|properties| self.project_state(&properties)) {
+            self.save_project_state(&state)?;
+        }
+        Ok(())
+    }
+    
+    pub fn undo(&mut self) -> Result<()> {
+        match self.journal.next_undo() {
+            Some(operation) => {
+                self.restore_snapshot(&operation.before)?;
+                println!("Undid '{}'.", operation.name);
+                self.journal.finish_undo();
+            }
+            None => println!("Nothing to undo."),
+        }
+        Ok(())
+    }
+    
+    pub fn redo(&mut self) -> Result<()> {
+        match self.journal.next_redo() {
+            Some(operation) => {
+                self.restore_snapshot(&operation.after)?;
+                println!("Redid '{}'.", operation.name);
+                self.journal.finish_redo();
+            }
+            None => println!("Nothing to redo."),
         }
         Ok(())
     }
     
+    pub fn fetch_latest_gradle(&self) -> Result<GradleVersion> {
+        self.api_request::<GradleVersion>(&format!("{}/current", self.config.api.gradle_versions))
+    }
+    
+    pub fn fetch_latest_loom(&self) -> Result<String> {
+        Ok(self.api_request::<LoomVersion>(&format!("{}/latest", self.config.api.loom_releases))?.tag_name)
+    }
+    
+    pub fn fetch_latest_loader(&self) -> Result<String> {
+        Ok(self.api_request::<Box<[FabricLoaderVersion]>>(&format!("{}/versions/loader", self.config.api.fabric_meta))?.iter().find(|v| v.stable).ok_or("No stable loader versions found.")?.version.clone())
+    }
+    
     pub fn fetch_version_info(&mut self) -> Result<()> {
-        let mut versions = self.api_request::<Box<[MinecraftVersion]>>("https://meta.fabricmc.net/v2/versions/game")?.iter().filter_map(|v| {
+        let mut versions = self.api_request::<Box<[GameVersion]>>(&format!("{}/versions/game", self.config.api.fabric_meta))?.iter().filter_map(|v| {
             if v.stable {
-                Some((v.version.parse().ok()?, 0u32))
+                Some((v.version.parse::<MinecraftVersion>().ok()?, 0u32))
             } else { None }
         }).collect::<Box<[_]>>();
         
-        for mapping in self.api_request::<Box<[YarnMappingsVersion]>>("https://meta.fabricmc.net/v2/versions/yarn")? {
-            if let Ok(version) = mapping.gameVersion.parse() {
-                if let Some(matching) = versions.iter_mut().find(|v| v.0 == version) {
-                    matching.1 = u32::max(matching.1, mapping.build);
+        let mut yarn_builds = BTreeMap::<String, Vec<u32>>::new();
+        for mapping in self.api_request::<Box<[YarnMappingsVersion]>>(&format!("{}/versions/yarn", self.config.api.fabric_meta))? {
+            if let Some(matching) = versions.iter_mut().find(|v| v.0.id == mapping.gameVersion) {
+                matching.1 = u32::max(matching.1, mapping.build);
+                yarn_builds.entry(matching.0.to_string()).or_default().push(mapping.build);
+            }
+        }
+        for builds in yarn_builds.values_mut() {
+            builds.sort();
+            builds.dedup();
+        }
+        self.yarn_builds = yarn_builds;
+        
+        let minecraft_versions = versions.iter().map(|(version, _)| version.clone()).collect::<Vec<_>>();
+        match self.fetch_manifest_info(&minecraft_versions) {
+            Ok(info) => {
+                self.java_versions = info.iter().filter_map(|(id, info)| Some((id.clone(), info.java_version?))).collect();
+                for (version, _) in &mut versions {
+                    version.release_time = info.get(&version.id).map(|info| info.release_time.clone());
                 }
             }
+            Err(e) => println!("Could not get Java versions from Mojang's version manifest, using the built-in table: {}", e),
         }
         
+        versions.sort_by(|(a, _), (b, _)| b.cmp(a));
         self.mc_versions = versions;
         Ok(())
     }
     
     pub fn clean_dependencies(&self) -> Result<()> {
-        clean_folder(self.cwd.join(LOCAL_MAVEN))?;
+        clean_folder(self.cwd.join(&self.config.paths.local_maven))?;
         Ok(())
     }
     
     pub fn test_version(&self, index: usize) -> Result<()> {
-        run_command(GRADLE, ["--stop"])?;
-        let contents = self.read_properties()?;
+        self.run_gradle(["--stop"])?;
+        let names = &self.config.properties;
+        let mut properties = self.read_properties()?;
         
-        let java_version = get_java_version(match simplify_range_set(self.parse_current_ranges(&contents)?).first() {
-            Some(first_range) => match &first_range.start {
-                Some(start) => if *start < self.mc_versions[index].0 {
-                    start
-                } else {
-                    &self.mc_versions[index].0
-                }
-                None => &self.mc_versions[index].0,
-            }
-            None => &self.mc_versions[index].0,
+        // Build with the Java version of the oldest confirmed version, so the jar still runs there
+        let ranges = self.parse_current_ranges(&properties)?;
+        let java_version = self.java_version(match self.mc_versions.iter().rev().map(|(v, _)| v).find(|v| ranges.contains(&v.semver)) {
+            Some(oldest) if *oldest < self.mc_versions[index].0 => oldest,
+            _ => &self.mc_versions[index].0,
         });
         
-        let contents = self.find_property(&contents, "minecraft_version")?.replace(&self.mc_versions[index].0.to_string());
-        let contents = self.find_property(&contents, "yarn_mappings")?.replace(&format!("{}+build.{}", self.mc_versions[index].0, self.mc_versions[index].1));
-        let contents = self.find_property(&contents, "java_version")?.replace(&java_version.to_string());
-        let contents = self.find_property(&contents, "enforce_range")?.replace("false");
-        self.write_properties(&contents)?;
+        for name in [&names.minecraft_version, &names.java_version, &names.enforce_range] {
+            self.find_property(&properties, name)?;
+        }
+        properties.set(&names.minecraft_version, &self.mc_versions[index].0.to_string());
+        if self.config.mappings == MappingMode::Yarn {
+            self.find_property(&properties, &names.yarn_mappings)?;
+            properties.set(&names.yarn_mappings, &format!("{}+build.{}", self.mc_versions[index].0, self.yarn_build(index)?));
+        }
+        properties.set(&names.java_version, &java_version.to_string());
+        properties.set(&names.enforce_range, "false");
+        if let Err(e) = self.select_loom(&mut properties, &self.mc_versions[index].0.semver) {
+            println!("Could not check the loom version: {}", e);
+        }
+        // Gradle has to run on the Java the tested version needs, which can be newer than the java_version to compile for
+        self.select_jdk(&mut properties, self.java_version(&self.mc_versions[index].0));
+        self.write_properties(&properties)?;
         
         self.fetch_dependencies()?;
         println!("Testing Minecraft version {}.", self.mc_versions[index].0);
         Ok(())
     }
     
+    /// Yarn build to test a Minecraft version with: the pinned one if there is one, else the latest.
+    pub fn yarn_build(&self, index: usize) -> Result<u32> {
+        let (version, latest) = &self.mc_versions[index];
+        Ok(Lockfile::load(&self.cwd)?.yarn_pins.get(&version.to_string()).copied().unwrap_or(*latest))
+    }
+    
+    pub fn list_yarn_builds(&self, index: usize) -> Result<()> {
+        let version = &self.mc_versions[index].0;
+        let pinned = Lockfile::load(&self.cwd)?.yarn_pins.get(&version.to_string()).copied();
+        let builds = self.yarn_builds.get(&version.to_string()).map(Vec::as_slice).unwrap_or_default();
+        if builds.is_empty() {
+            println!("No yarn builds known for Minecraft {}.", version);
+            return Ok(())
+        }
+        print!("Yarn builds for Minecraft {}: ", version);
+        for (i, build) in builds.iter().enumerate() {
+            if i > 0 { print!(", "); }
+            print!("{}{}", build, if Some(*build) == pinned { " (pinned)" } else { "" });
+        }
+        println!();
+        Ok(())
+    }
+    
+    /// Pin the yarn build of the current Minecraft version, or unpin it with `None`, and switch the mappings to it.
+    pub fn pin_yarn(&self, build: Option<u32>) -> Result<()> {
+        let names = &self.config.properties;
+        let mut properties = self.read_properties()?;
+        let version = self.find_property(&properties, &names.minecraft_version)?;
+        let index = self.version_index(&version)?;
+        let version = &self.mc_versions[index].0;
+        if let Some(build) = build && !self.yarn_builds.get(&version.to_string()).is_some_and(|builds| builds.contains(&build)) {
+            return Err(format!("Minecraft {} has no yarn build {}. Use 'yarn' to list the builds.", version, build).into())
+        }
+        
+        let mut lockfile = Lockfile::load(&self.cwd)?;
+        match build {
+            Some(build) => {
+                lockfile.yarn_pins.insert(version.to_string(), build);
+                println!("Pinned yarn build {} for Minecraft {}.", build, version);
+            }
+            None => if lockfile.yarn_pins.remove(&version.to_string()).is_some() {
+                println!("Unpinned the yarn build for Minecraft {}.", version);
+            }
+        }
+        lockfile.write(&self.cwd)?;
+        
+        if self.config.mappings == MappingMode::Yarn {
+            properties.set(&names.yarn_mappings, &format!("{}+build.{}", version, self.yarn_build(index)?));
+            self.write_properties(&properties)?;
+        }
+        Ok(())
+    }
+    
+    /// Find the newest version of a dependency that supports the Minecraft version closest to `version`, picked by `closest_game_version`.
+    fn find_fallback_dependency(&self, name: &str, version: &MinecraftVersion) -> Result<Option<(ProjectVersion, MinecraftVersion)>> {
+        let versions = self.api_request::<Vec<ProjectVersion>>(&format!("{}/project/{}/version?loaders=[\"fabric\"]", self.config.api.modrinth, name))?;
+        let game_versions = versions.iter()
+            .flat_map(|v| v.game_versions.iter().filter_map(|s| s.parse::<MinecraftVersion>().ok()))
+            .filter(MinecraftVersion::is_release)
+            .collect::<Vec<_>>();
+        
+        let Some(fallback) = closest_game_version(version, &game_versions).cloned() else { return Ok(None) };
+        
+        Ok(versions.into_iter()
+            .find(|v| v.game_versions.iter().any(|s| s.parse::<MinecraftVersion>().is_ok_and(|s| s == fallback)))
+            .map(|v| (v, fallback)))
+    }
+    
+    /// Folder of Fabric API on Fabric's maven.
+    fn fabric_api_maven(&self) -> String {
+        format!("{}/net/fabricmc/fabric-api/fabric-api", self.config.api.fabric_maven)
+    }
+    
+    /// Every Fabric API version listed in the maven metadata.
+    fn fabric_api_builds(&self) -> Result<Vec<String>> {
+        let metadata = self.api_request_text(&format!("{}/maven-metadata.xml", self.fabric_api_maven()))?;
+        Ok(maven_metadata_versions(&metadata))
+    }
+    
+    /// Find the newest Fabric API build for a Minecraft version from Fabric's maven metadata, checking that its jar is actually published.
+    pub fn find_fabric_api(&self, version: &MinecraftVersion) -> Result<Option<ProjectVersion>> {
+        let fabric_api_maven = self.fabric_api_maven();
+        let all_builds = self.fabric_api_builds()?;
+        for build in fabric_api_builds_for(&all_builds, version) {
+            let url = format!("{fabric_api_maven}/{build}/fabric-api-{build}.jar");
+            if self.api_file_exists(&url)? {
+                return Ok(Some(ProjectVersion {
+                    game_versions: Box::new([version.to_string()]),
+                    version_number: build.to_owned(),
+                    files: Box::new([ProjectFile { url }]),
+                }))
+            }
+            println!("Fabric API {} is listed in the maven metadata but not published, skipping it.", build);
+        }
+        Ok(None)
+    }
+    
+    /// Minecraft versions a dependency has builds for.
+    pub fn dependency_game_versions(&self, name: &str) -> Result<VersionRangeSet> {
+        let versions: Vec<MinecraftVersion> = if name == "fabric-api" {
+            self.fabric_api_builds()?.iter().filter_map(|build| build.split_once('+')?.1.parse().ok()).collect()
+        } else {
+            let versions = self.api_request::<Vec<ProjectVersion>>(&format!("{}/project/{}/version?loaders=[\"fabric\"]", self.config.api.modrinth, name))?;
+            versions.iter().flat_map(|v| v.game_versions.iter().filter_map(|s| s.parse().ok())).collect()
+        };
+        Ok(VersionRangeSet::from_versions(versions.iter().map(|version| &version.semver)))
+    }
+    
+    /// Find the version of a dependency to use for a Minecraft version, falling back to a nearby version if allowed.
+    /// Returns `None` if the dependency does not support the version.
+    fn resolve_dependency(&self, name: &str, version: &MinecraftVersion, allow_fallback: bool) -> Result<Option<(ProjectVersion, Option<MinecraftVersion>)>> {
+        if name == "fabric-api" {
+            return self.find_fabric_api(version)
+                .map(|dependency_version| dependency_version.map(|v| (v, None)))
+                .map_err(|e| format!("Could not get version info for Fabric API from the Fabric maven: {}", e).into())
+        }
+        (|| {
+            let versions = self.api_request::<Vec<ProjectVersion>>(&format!("{}/project/{}/version?loaders=[\"fabric\"]&game_versions=[\"{}\"]", self.config.api.modrinth, name, version))?;
+            Ok(match versions.into_iter().next() {
+                Some(dependency_version) => Some((dependency_version, None)),
+                None if allow_fallback => self.find_fallback_dependency(name, version)?.map(|(dependency_version, fallback)| (dependency_version, Some(fallback))),
+                None => None,
+            })
+        })().map_err(|e: Error| format!("Could not get version info for dependency '{}' from modrinth: {}", name, e).into())
+    }
+    
+    /// Version of a dependency for the oldest confirmed Minecraft version, from the previous lockfile if it was resolved for the same version.
+    fn oldest_dependency_version(&self, name: &str, oldest: &MinecraftVersion, previous: &Lockfile, allow_fallback: bool) -> Result<Option<String>> {
+        let locked = previous.dependencies.iter().find(|dependency| dependency.name == name).and_then(|dependency| dependency.oldest_confirmed.as_ref());
+        if let Some((locked_for, locked_version)) = locked && *locked_for == oldest.id {
+            return Ok(Some(locked_version.clone()))
+        }
+        Ok(self.resolve_dependency(name, oldest, allow_fallback)?.map(|(dependency_version, _)| dependency_version.version_number))
+    }
+    
     pub fn fetch_dependencies(&self) -> Result<()> {
-        let contents = self.read_properties()?;
-        let version = self.find_property(&contents, "minecraft_version")?.substring.parse::<SemanticVersion>()?;
-        let mut new_contents = String::new();
+        let names = &self.config.properties;
+        let mut properties = self.read_properties()?;
+        let version = self.find_property(&properties, &names.minecraft_version)?.parse::<MinecraftVersion>()?;
+        let fallback_dependencies = self.find_list_property(&properties, &names.fallback_dependencies);
+        let optional_dependencies = self.find_list_property(&properties, &names.optional_dependencies);
+        let marker = self.find_ralli_marker(&properties).ok_or("No ralli marker comment found in gradle properties.")?;
+        let previous = Lockfile::load(&self.cwd)?;
+        let ranges = self.parse_current_ranges(&properties)?;
+        let oldest_confirmed = self.mc_versions.iter().rev().map(|(v, _)| v).find(|v| ranges.contains(&v.semver)).filter(|oldest| *oldest < &version);
+        let mut resolved = vec![];
+        let mut disabled = vec![];
         
-        let download_jars_into = self.cwd.join(LOCAL_MAVEN);
+        // Resolve every dependency before touching any files, so an unsupported dependency leaves the project as it was.
+        for entry in properties.entries() {
+            if entry.line < marker || names.is_ralli_property(&entry.key) { continue }
+            let name = entry.key;
+            let allow_fallback = fallback_dependencies.contains(&name);
+            match self.resolve_dependency(&name, &version, allow_fallback)? {
+                Some((dependency_version, fallback)) => {
+                    let oldest = match oldest_confirmed {
+                        Some(oldest) => self.oldest_dependency_version(&name, oldest, &previous, allow_fallback)?.map(|oldest_version| (oldest.id.clone(), oldest_version)),
+                        None => None,
+                    };
+                    resolved.push((name, dependency_version, fallback, oldest));
+                }
+                None if optional_dependencies.contains(&name) => {
+                    println!("Skipping optional dependency '{}', it does not support Minecraft version {}.", name, version);
+                    disabled.push(name);
+                }
+                None => return Err(format!("Dependency '{}' does not support Minecraft version {}.", name, version).into()),
+            }
+        }
+        
+        let download_jars_into = self.cwd.join(&self.config.paths.local_maven);
         std::fs::create_dir_all(&download_jars_into)?;
         
-        let copy_jars_into = self.cwd.join("run/mods");
+        let copy_jars_into = self.cwd.join(&self.config.paths.run_mods);
         std::fs::create_dir_all(&copy_jars_into)?;
         clean_folder(&copy_jars_into)?;
         
-        let mut lines = contents.split('\n');
-        loop {
-            if let Some(line) = lines.next() {
-                new_contents.push_str("\n");
-                new_contents.push_str(line);
-                if let Some((_, part)) = line.split_once('#') {
-                    if part.trim_start().to_lowercase().starts_with("ralli") { break }
-                }
-            } else { break }
-        }
-        
-        for line in lines {
-            new_contents.push_str("\n");
-            let (line_before_comment, comment) = line.split_once("#").map(|(l, c)| (l, Some(c))).unwrap_or((line, None));
-            if let Some((name, _)) = line_before_comment.split_once("=") {
-                match name.trim() {
-                    "loom_version" | "loader_version" | "minecraft_compatible_range" | "enforce_range" | "minecraft_version" | "yarn_mappings" | "java_version" => {
-                        new_contents.push_str(line);
-                    }
-                    name => {
-                        let versions = self.api_request::<Box<[ProjectVersion]>>(&format!("https://api.modrinth.com/v2/project/{}/version?loaders=[\"fabric\"]&game_versions=[\"{}\"]", name, version));
-                        let versions = versions.map_err(|e| format!("Cound not get version info for dependency '{}' from modrinth: {}", name, e))?;
-                        let dependency_version = versions.get(0).ok_or(format!("Dependency '{}' does not support Minecraft version {}.", name, version))?;
-                        
-                        new_contents.push_str(name);
-                        new_contents.push_str("=");
-                        new_contents.push_str(&dependency_version.version_number.to_string());
-                        if let Some(comment) = comment {
-                            new_contents.push_str(" #");
-                            new_contents.push_str(comment);
-                        } else {
-                            if line_before_comment.ends_with("\r") { new_contents.push_str("\r"); }
-                        }
-                        
-                        let mut downloaded = false;
-                        if let Some(file) = dependency_version.files.first() {
-                            let file_name = format!("{}-{}.jar", name, dependency_version.version_number);
-                            let path = download_jars_into.join(&file_name);
-                            if !std::fs::exists(&path)? {
-                                self.api_download_file(&file.url, &path).map_err(|e| format!("Cound not download dependency '{}-{}': {}", name, dependency_version.version_number, e))?;
-                                downloaded = true;
-                            }
-                            
-                            std::fs::copy(&path, &copy_jars_into.join(&file_name))?;
-                        }
-                        
-                        print!("{} '{}-{}', supports: ", if downloaded {"Fetched"} else {"Already have"}, name, dependency_version.version_number);
-                        for (i, version) in dependency_version.game_versions.iter().enumerate() {
-                            if i > 0 { print!(", "); }
-                            print!("{}", version);
-                        }
-                        // for range in simplify_range_set(dependency_version.game_versions.iter().filter_map(|s| s.parse().ok()).collect()) {}
-                        println!();
-                    }
+        let mut lockfile = Lockfile { minecraft_version: version.to_string(), dependencies: vec![], disabled: disabled.clone(), yarn_pins: previous.yarn_pins };
+        for (name, dependency_version, fallback, oldest) in resolved {
+            let mut downloaded = false;
+            if let Some(file) = dependency_version.files.first() {
+                let file_name = format!("{}-{}.jar", name, dependency_version.version_number);
+                let path = download_jars_into.join(&file_name);
+                if !std::fs::exists(&path)? {
+                    self.api_download_file(&file.url, &path).map_err(|e| format!("Could not download dependency '{}-{}': {}", name, dependency_version.version_number, e))?;
+                    downloaded = true;
                 }
-            } else {
-                new_contents.push_str(line);
+                
+                std::fs::copy(&path, copy_jars_into.join(&file_name))?;
+            }
+            
+            if let Some(fallback) = &fallback {
+                print!("FALLBACK: '{}' has no version for Minecraft {}, using the version for {}. ", name, version, fallback);
+            }
+            print!("{} '{}-{}', supports: ", if downloaded {"Fetched"} else {"Already have"}, name, dependency_version.version_number);
+            for (i, version) in dependency_version.game_versions.iter().enumerate() {
+                if i > 0 { print!(", "); }
+                print!("{}", version);
             }
+            // for range in simplify_range_set(dependency_version.game_versions.iter().filter_map(|s| s.parse().ok()).collect()) {}
+            println!();
+            
+            properties.set(&name, &dependency_version.version_number);
+            let minimum = minimum_dependency_version(&dependency_version.version_number, oldest.as_ref().map(|(_, oldest_version)| oldest_version.as_str()));
+            let range = SemanticVersionRange { start: minimum, ..Default::default() };
+            self.set_ralli_property(&mut properties, &names.dependency_range(&name), &format!("\"{}\"", range));
+            lockfile.dependencies.push(LockedDependency {
+                name,
+                version: dependency_version.version_number,
+                fallback: fallback.map(|v| v.to_string()),
+                oldest_confirmed: oldest,
+            });
         }
         
-        self.write_properties(new_contents.strip_prefix("\n").unwrap_or(&new_contents))?;
+        // build.gradle moves disabled dependencies to suggests, where any version will do
+        for name in &disabled {
+            self.set_ralli_property(&mut properties, &names.dependency_range(name), "\"*\"");
+        }
+        self.set_ralli_property(&mut properties, &names.disabled_dependencies, &disabled.join(","));
+        self.write_properties(&properties)?;
+        lockfile.write(&self.cwd)?;
         Ok(())
     }
     
     pub fn confirm_version(&self) -> Result<()> {
-        let contents = self.read_properties()?;
-        let ranges_part = self.find_property(&contents, "minecraft_compatible_range")?;
-        let version_part = self.find_property(&contents, "minecraft_version")?;
-        
-        let version = version_part.substring.parse()?;
-        let index = self.mc_versions.iter().position(|(v, _)| *v == version).ok_or("Current version not found in the Minecraft version list.")?;
+        let names = &self.config.properties;
+        let mut properties = self.read_properties()?;
+        let version = self.find_property(&properties, &names.minecraft_version)?.parse::<MinecraftVersion>()?;
+        if !self.mc_versions.iter().any(|(v, _)| *v == version) { return Err("Current version not found in the Minecraft version list.".into()) }
         
-        let mut ranges = self.parse_ranges_slice(&ranges_part)?;
-        ranges.push(SemanticVersionRange {
-            start: Some(version.clone()),
-            end: index.checked_sub(1).map(|i| self.mc_versions[i].0.clone()),
+        let mut ranges = self.parse_current_ranges(&properties)?;
+        ranges.insert(SemanticVersionRange {
+            start: Some(version.semver.clone()),
+            end: Some(version.semver.clone()),
+            start_exclusive: false,
+            end_inclusive: true,
         });
         
-        let mut new_ranges_string = String::from("[");
-        for (i, range) in simplify_range_set(ranges).iter().enumerate() {
-            if i != 0 { new_ranges_string.push_str(", "); }
-            new_ranges_string.push_str("\"");
-            new_ranges_string.push_str(&range.to_string());
-            new_ranges_string.push_str("\"");
-        }
-        new_ranges_string.push_str("]");
-        
-        self.write_properties(&ranges_part.replace(&new_ranges_string))?;
+        let known = self.mc_versions.iter().map(|(v, _)| v.semver.clone()).collect::<Vec<_>>();
+        properties.set(&names.compatible_range, &ranges.compacted(&known).to_string());
+        self.write_properties(&properties)?;
         println!("Added Minecraft version {} to the compatibility range.", version);
         Ok(())
     }
     
+    pub fn status(&self) -> Result<()> {
+        let names = &self.config.properties;
+        let properties = self.read_properties()?;
+        let get = |name: &str| properties.get(name).unwrap_or_else(|| "?".to_owned());
+        
+        print!("Minecraft {}", get(&names.minecraft_version));
+        if self.config.mappings == MappingMode::Yarn { print!(", yarn {}", get(&names.yarn_mappings)); }
+        println!(", Java {}, loom {}, loader {}", get(&names.java_version), get(&names.loom_version), get(&names.loader_version));
+        
+        print!("Compatible with: ");
+        match self.get_current_ranges() {
+            Ok(ranges) if ranges.is_empty() => print!("no confirmed versions"),
+            Ok(ranges) => for (i, range) in ranges.ranges.iter().enumerate() {
+                if i > 0 { print!(", "); }
+                print!("{}", range);
+            }
+            Err(e) => print!("{}", e),
+        }
+        println!("{}", if get(&names.enforce_range) == "true" { " (enforced)" } else { "" });
+        
+        if let Some(marker) = self.find_ralli_marker(&properties) {
+            let disabled = self.find_list_property(&properties, &names.disabled_dependencies);
+            print!("Dependencies: ");
+            for (i, entry) in properties.entries().into_iter().filter(|entry| entry.line > marker && !names.is_ralli_property(&entry.key)).enumerate() {
+                if i > 0 { print!(", "); }
+                print!("{} {}{}", entry.key, entry.value, if disabled.contains(&entry.key) { " (disabled)" } else { "" });
+            }
+            println!();
+        }
+        
+        // Edits made by hand since ralli last saved the project state
+        if let Ok(Some(saved)) = self.load_project_state() && let Ok(current) = self.project_state(&properties) {
+            let changes = state_changes(&saved, &current);
+            if !changes.is_empty() {
+                println!("Changed outside ralli since its last command: {}", changes.join(", "));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Default versions to sweep: from the oldest confirmed version (or the current version) up to the newest known version.
+    pub fn sweep_bounds(&self, from: Option<&str>, to: Option<&str>) -> Result<(usize, usize)> {
+        let oldest = match from {
+            Some(from) => self.version_index(from)?,
+            None => {
+                let ranges = self.get_current_ranges()?;
+                let confirmed = self.mc_versions.iter().rposition(|(v, _)| ranges.contains(&v.semver));
+                match confirmed {
+                    Some(index) => index,
+                    None => self.version_index(&self.find_property(&self.read_properties()?, &self.config.properties.minecraft_version)?)?,
+                }
+            }
+        };
+        let newest = match to {
+            Some(to) => self.version_index(to)?,
+            None => 0,
+        };
+        if newest > oldest { return Err("The first version of a sweep has to be older than the last.".into()) }
+        Ok((oldest, newest))
+    }
+    
+    /// Test and build every known Minecraft version from `oldest` up to `newest` (indices into `mc_versions`),
+    /// then switch back to the version that was being tested before.
+    pub fn sweep(&self, oldest: usize, newest: usize) -> Result<SweepResults> {
+        let current = self.find_property(&self.read_properties()?, &self.config.properties.minecraft_version)?;
+        let mut results = vec![];
+        for index in (newest..=oldest).rev() {
+            let version = self.mc_versions[index].0.clone();
+            let outcome = match self.test_version(index) {
+                Ok(()) => if self.run_gradle_status(["clean", "build"])? { TestOutcome::Passed } else { TestOutcome::Failed },
+                Err(e) => TestOutcome::Unsupported(e.to_string()),
+            };
+            println!("Minecraft {}: {}", version, outcome);
+            results.push((version, outcome));
+        }
+        
+        if let Ok(index) = self.version_index(&current) {
+            self.test_version(index)?;
+        }
+        Ok(results)
+    }
+    
     pub fn release(&self) -> Result<()> {
-        let contents = self.read_properties()?;
-        let ranges = simplify_range_set(self.parse_current_ranges(&contents)?);
+        if self.config.release.require_bump { self.check_release_bump()?; }
+        let ranges = self.get_current_ranges()?;
         
         let mut versions = vec![];
         let mut first_index = None;
         for (i, (version, _)) in self.mc_versions.iter().enumerate().rev() {
-            for range in &ranges {
-                if range.contains(version) {
-                    versions.push(version);
-                    if first_index.is_none() {
-                        first_index = Some(i);
-                    }
-                    break
+            if ranges.contains(&version.semver) {
+                versions.push(version);
+                if first_index.is_none() {
+                    first_index = Some(i);
                 }
             }
         }
@@ -341,9 +774,10 @@ impl App {
         // let contents = self.find_property(&contents, "yarn_mappings")?.replace(&format!("{}+build.{}", version, mapping));
         // let contents = self.find_property(&contents, "java_version")?.replace(&get_java_version(&version).to_string());
         self.test_version(index)?;
-        let contents = self.read_properties()?;
-        let contents = self.find_property(&contents, "enforce_range")?.replace("true");
-        self.write_properties(&contents)?;
+        let mut properties = self.read_properties()?;
+        self.find_property(&properties, &self.config.properties.enforce_range)?;
+        properties.set(&self.config.properties.enforce_range, "true");
+        self.write_properties(&properties)?;
         
         print!("Ready to build release for Minecraft versions: ");
         for (i, version) in versions.iter().enumerate() {
@@ -0,0 +1,122 @@
+use crate::{app::App, common::*, semantic_version::{SemanticVersion, is_identifiers}};
+
+
+/// Part of the mod version to increment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    /// Prerelease with the given tag, like `beta` for `1.2.3-beta.1`.
+    Pre(String),
+}
+
+impl std::str::FromStr for Bump {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split_whitespace();
+        let bump = match (parts.next(), parts.next()) {
+            (Some("major"), None) => Bump::Major,
+            (Some("minor"), None) => Bump::Minor,
+            (Some("patch"), None) => Bump::Patch,
+            (Some("pre"), Some(tag)) if is_identifiers(tag) => Bump::Pre(tag.to_owned()),
+            (Some("pre"), Some(tag)) => return Err(format!("Invalid prerelease tag '{}'.", tag).into()),
+            _ => return Err("Expected major, minor, patch or pre <tag>.".into()),
+        };
+        if parts.next().is_some() { return Err("Expected major, minor, patch or pre <tag>.".into()) }
+        Ok(bump)
+    }
+}
+
+/// The version after `version` for a bump.
+/// Bumping a prerelease to the release it leads up to just drops the prerelease, so `1.3.0-beta.2` bumps to `1.3.0` with minor or patch.
+/// A prerelease bump counts up prereleases of the same tag and otherwise starts one at 1, on the next patch for a release.
+pub fn bump_version(version: &SemanticVersion, bump: &Bump) -> Result<SemanticVersion> {
+    let (major, minor, patch) = (version.major, version.minor, version.patch);
+    let prerelease = version.release.is_some();
+    let below_minor_zero = patch == 0 && version.extra.iter().all(|&n| n == 0);
+    let bumped = match bump {
+        Bump::Major if prerelease && minor == 0 && below_minor_zero => SemanticVersion::new(major, 0, 0),
+        Bump::Major => SemanticVersion::new(major + 1, 0, 0),
+        Bump::Minor if prerelease && below_minor_zero => SemanticVersion::new(major, minor, 0),
+        Bump::Minor => SemanticVersion::new(major, minor + 1, 0),
+        Bump::Patch if prerelease => SemanticVersion { extra: version.extra.clone(), ..SemanticVersion::new(major, minor, patch) },
+        Bump::Patch => SemanticVersion::new(major, minor, patch + 1),
+        Bump::Pre(tag) => {
+            let counter = match &version.release {
+                Some(release) if release == tag => Some(0),
+                Some(release) => release.strip_prefix(tag.as_str()).and_then(|rest| rest.strip_prefix('.')).and_then(|n| n.parse::<u32>().ok()),
+                None => None,
+            };
+            let mut bumped = match version.release {
+                Some(_) => SemanticVersion { build: None, ..version.clone() },
+                None => SemanticVersion::new(major, minor, patch + 1),
+            };
+            bumped.release = Some(format!("{}.{}", tag, counter.map_or(1, |n| n + 1)));
+            bumped
+        }
+    };
+    if bumped <= *version {
+        return Err(format!("Bumping {} to {} would not make it newer.", version, bumped).into())
+    }
+    Ok(bumped)
+}
+
+/// Bump a version as written, keeping as many number components as it had, so `1.2.3` bumps to `1.3.0` rather than `1.3`.
+pub fn bump_version_text(text: &str, bump: &Bump) -> Result<String> {
+    let version = text.trim().parse::<SemanticVersion>()?;
+    let bumped = bump_version(&version, bump)?;
+    let written_components = text.trim().split(['-', '+']).next().unwrap_or_default().split('.').count();
+    let mut components = bumped.components().collect::<Vec<_>>();
+    while components.len() > written_components && components.last() == Some(&0) { components.pop(); }
+    components.resize(components.len().max(written_components), 0);
+    let mut result = components.iter().map(u32::to_string).collect::<Vec<_>>().join(".");
+    if let Some(release) = &bumped.release { result += &format!("-{}", release); }
+    Ok(result)
+}
+
+impl App {
+    pub fn mod_version(&self) -> Result<SemanticVersion> {
+        let properties = self.read_properties()?;
+        self.find_property(&properties, &self.config.properties.mod_version)?.trim().parse()
+    }
+    
+    /// Increment `mod_version` in the gradle properties.
+    pub fn bump_mod_version(&self, bump: &Bump) -> Result<()> {
+        let mut properties = self.read_properties()?;
+        let name = &self.config.properties.mod_version;
+        let version = self.find_property(&properties, name)?.trim().to_owned();
+        let bumped = bump_version_text(&version, bump)?;
+        properties.set(name, &bumped);
+        self.write_properties(&properties)?;
+        println!("Bumped {} from {} to {}", name, version, bumped);
+        Ok(())
+    }
+    
+    /// Name and version of the latest release tag reachable in git, `None` if there is none.
+    pub fn last_release_tag(&self) -> Result<Option<(String, SemanticVersion)>> {
+        let prefix = &self.config.release.tag_prefix;
+        let output = std::process::Command::new("git")
+            .args(["describe", "--tags", "--abbrev=0", "--match", &format!("{}*", prefix)])
+            .current_dir(&self.cwd)
+            .output()?;
+        // git fails when there is no matching tag, or no repository at all
+        if !output.status.success() { return Ok(None) }
+        let tag = String::from_utf8(output.stdout)?.trim().to_owned();
+        let version = tag[prefix.len()..].parse().map_err(|e| format!("Release tag '{}' isn't a version: {}", tag, e))?;
+        Ok(Some((tag, version)))
+    }
+    
+    /// Make sure `mod_version` was bumped since the last release tag.
+    pub fn check_release_bump(&self) -> Result<()> {
+        let version = self.mod_version()?;
+        let Some((tag, released)) = self.last_release_tag()? else {
+            println!("No release tag found, so {} {} is the first release.", self.config.properties.mod_version, version);
+            return Ok(())
+        };
+        if version <= released {
+            return Err(format!("{} {} hasn't been bumped since release {}. Use 'bump major|minor|patch' first.", self.config.properties.mod_version, version, tag).into())
+        }
+        Ok(())
+    }
+}
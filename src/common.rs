@@ -3,41 +3,83 @@
 pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub struct SubstringRef<'a> {
-    pub before: &'a str,
-    pub substring: &'a str,
-    pub after: &'a str,
-}
-
-impl<'a> SubstringRef<'a> {
-    pub fn find(source: &'a str, start_pattern: &str, end_pattern: &str) -> Option<Self> {
-        let (before, rest) = source.split_at_checked(source.find(start_pattern)? + start_pattern.len())?;
-        let (substring, after) = rest.split_at_checked(rest.find(end_pattern)?)?;
-        Some(Self { before, substring, after })
-    }
-    pub fn replace(&self, insert_string: &str) -> String {
-        String::from(self.before) + insert_string + self.after
-    }
-}
-
 pub fn clean_folder(path: impl AsRef<std::path::Path>) -> Result<()> {
-    for entry in std::fs::read_dir(path)? {
-        if let Ok(entry) = entry {
-            if let Ok(t) = entry.file_type() {
-                if t.is_file() && !entry.file_name().to_string_lossy().starts_with("_") {
-                    std::fs::remove_file(entry.path())?;
-                }
-            }
+    for entry in std::fs::read_dir(path)?.flatten() {
+        if let Ok(t) = entry.file_type() && t.is_file() && !entry.file_name().to_string_lossy().starts_with("_") {
+            std::fs::remove_file(entry.path())?;
         }
     }
     Ok(())
 }
 
 pub fn run_command(host: impl AsRef<std::ffi::OsStr>, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> Result<()> {
-    std::process::Command::new(host).args(args)
+    run_command_status(host, args)?;
+    Ok(())
+}
+
+/// Run a command like `run_command`, returning whether it exited successfully.
+pub fn run_command_status(host: impl AsRef<std::ffi::OsStr>, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> Result<bool> {
+    run_command_in(".", host, args)
+}
+
+/// Run a command in another working directory, returning whether it exited successfully.
+pub fn run_command_in(dir: impl AsRef<std::path::Path>, host: impl AsRef<std::ffi::OsStr>, args: impl IntoIterator<Item = impl AsRef<std::ffi::OsStr>>) -> Result<bool> {
+    Ok(std::process::Command::new(host).args(args)
+        .current_dir(dir)
         .stdin(std::process::Stdio::inherit())
         .stdout(std::process::Stdio::inherit())
         .stderr(std::process::Stdio::inherit())
-        .spawn()?.wait()?;
+        .spawn()?.wait()?.success())
+}
+
+/// Implement `Serialize` and `Deserialize` through `Display` and `FromStr`, so values are stored as their canonical strings.
+macro_rules! serde_as_string {
+    ($($t:ty),*) => {$(
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+        
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                <String as serde::Deserialize>::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+pub(crate) use serde_as_string;
+
+/// Write a file by writing a temporary file next to it and renaming it into place, so the file is never left half-written.
+pub fn write_atomic(path: impl AsRef<std::path::Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let path = path.as_ref();
+    let file_name = path.file_name().ok_or("Cannot write to a path without a file name.")?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    {
+        let mut file = std::fs::File::create(&temp_path)?;
+        std::io::Write::write_all(&mut file, contents.as_ref())?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&temp_path, path).inspect_err(|_| { let _ = std::fs::remove_file(&temp_path); })?;
     Ok(())
 }
+
+/// Current UTC time formatted as `YYYYMMDD-HHMMSS-mmm`, which sorts chronologically.
+pub fn timestamp() -> String {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs();
+    let (days, time) = (seconds / 86400, seconds % 86400);
+    
+    // Civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}", year, month, day, time / 3600, time / 60 % 60, time % 60, now.subsec_millis())
+}
@@ -0,0 +1,218 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{common::*, toolchain::LoomRequirements};
+
+pub const CONFIG_FILE: &str = "ralli.toml";
+
+
+/// Project settings for ralli itself, read from `ralli.toml` in the project folder. Every setting is optional.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub properties: PropertyNames,
+    pub paths: Paths,
+    pub api: Endpoints,
+    pub mappings: MappingMode,
+    pub startup: StartupUpdates,
+    pub jdk: JdkSettings,
+    pub release: ReleaseSettings,
+    /// Extra or corrected entries for the loom compatibility matrix.
+    pub loom_compatibility: Vec<LoomRequirements>,
+}
+
+/// Names of the gradle properties ralli reads and writes.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PropertyNames {
+    pub compatible_range: String,
+    pub enforce_range: String,
+    pub minecraft_version: String,
+    /// Version of the mod itself, which the 'bump' command increments.
+    pub mod_version: String,
+    pub yarn_mappings: String,
+    pub java_version: String,
+    pub loom_version: String,
+    pub loader_version: String,
+    pub fallback_dependencies: String,
+    pub optional_dependencies: String,
+    pub disabled_dependencies: String,
+    /// Suffix of the properties holding the version range of each dependency for fabric.mod.json.
+    pub dependency_range_suffix: String,
+}
+
+impl Default for PropertyNames {
+    fn default() -> Self {
+        Self {
+            compatible_range: "minecraft_compatible_range".to_owned(),
+            enforce_range: "enforce_range".to_owned(),
+            minecraft_version: "minecraft_version".to_owned(),
+            mod_version: "mod_version".to_owned(),
+            yarn_mappings: "yarn_mappings".to_owned(),
+            java_version: "java_version".to_owned(),
+            loom_version: "loom_version".to_owned(),
+            loader_version: "loader_version".to_owned(),
+            fallback_dependencies: "fallback_dependencies".to_owned(),
+            optional_dependencies: "optional_dependencies".to_owned(),
+            disabled_dependencies: "disabled_dependencies".to_owned(),
+            dependency_range_suffix: "_version_range".to_owned(),
+        }
+    }
+}
+
+impl PropertyNames {
+    /// Properties after the ralli marker comment that are managed by ralli rather than being dependencies.
+    pub fn ralli_properties(&self) -> [&str; 10] {
+        [
+            &self.loom_version,
+            &self.loader_version,
+            &self.compatible_range,
+            &self.enforce_range,
+            &self.minecraft_version,
+            &self.yarn_mappings,
+            &self.java_version,
+            &self.fallback_dependencies,
+            &self.optional_dependencies,
+            &self.disabled_dependencies,
+        ]
+    }
+    
+    /// Whether a property after the ralli marker is managed by ralli rather than being a dependency.
+    pub fn is_ralli_property(&self, name: &str) -> bool {
+        self.ralli_properties().contains(&name) || name.ends_with(&self.dependency_range_suffix)
+    }
+    
+    /// Name of the property holding the version range of a dependency, usable as a groovy template variable.
+    pub fn dependency_range(&self, dependency: &str) -> String {
+        format!("{}{}", dependency.replace(['-', '.'], "_"), self.dependency_range_suffix)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Paths {
+    /// Command used to run gradle.
+    pub gradle: String,
+    pub gradle_wrapper_properties: String,
+    /// Folder dependency jars are downloaded into, which build.gradle uses as a flatDir repository.
+    pub local_maven: String,
+    /// Folder the dependency jars are copied into for the test client.
+    pub run_mods: String,
+    pub backups: String,
+    /// File the release time and Java version of each Minecraft version are cached in.
+    pub version_cache: String,
+    /// File the published gradle wrapper jar checksums are cached in.
+    pub wrapper_checksum_cache: String,
+    /// File the typed state of the project is saved to after every recorded command.
+    pub state: String,
+    /// Text a comment in gradle.properties starts with to mark the start of the ralli driven values.
+    pub marker: String,
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self {
+            gradle: "./gradlew.bat".to_owned(),
+            gradle_wrapper_properties: "gradle/wrapper/gradle-wrapper.properties".to_owned(),
+            local_maven: "local_maven".to_owned(),
+            run_mods: "run/mods".to_owned(),
+            backups: ".ralli/backups".to_owned(),
+            version_cache: ".ralli/versions.json".to_owned(),
+            wrapper_checksum_cache: ".ralli/wrapper-checksums.json".to_owned(),
+            state: ".ralli/state.json".to_owned(),
+            marker: "ralli".to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+    pub gradle_versions: String,
+    pub gradle_distributions: String,
+    pub loom_releases: String,
+    pub fabric_meta: String,
+    pub fabric_maven: String,
+    pub modrinth: String,
+    pub mojang_version_manifest: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            gradle_versions: "https://services.gradle.org/versions".to_owned(),
+            gradle_distributions: "https://services.gradle.org/distributions".to_owned(),
+            loom_releases: "https://api.github.com/repos/FabricMC/fabric-loom/releases".to_owned(),
+            fabric_meta: "https://meta.fabricmc.net/v2".to_owned(),
+            fabric_maven: "https://maven.fabricmc.net".to_owned(),
+            modrinth: "https://api.modrinth.com/v2".to_owned(),
+            mojang_version_manifest: "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json".to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MappingMode {
+    /// Yarn mappings, written to the `yarn_mappings` property for each tested version.
+    #[default]
+    Yarn,
+    /// Official Mojang mappings, which need no per-version property.
+    Mojang,
+}
+
+/// Tool updates done when ralli starts, all off unless turned on here. The 'update' command does them on demand.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StartupUpdates {
+    pub stop_daemons: bool,
+    pub gradle: bool,
+    pub loom: bool,
+    pub loader: bool,
+}
+
+/// How ralli makes gradle run on a JDK matching the tested Minecraft version.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct JdkSettings {
+    pub selection: JdkSelection,
+    /// Extra folders to look for JDKs in, either JDKs themselves or folders of JDKs.
+    pub search_paths: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JdkSelection {
+    /// Set `org.gradle.java.home` to the best matching installed JDK.
+    #[default]
+    JavaHome,
+    /// List every installed JDK in `org.gradle.java.installations.paths`, for builds using a java toolchain.
+    Toolchain,
+    /// Leave the JDK to the environment.
+    Off,
+}
+
+/// Checks done by the 'release' command.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReleaseSettings {
+    /// Refuse to release until `mod_version` is newer than the version of the last release tag.
+    pub require_bump: bool,
+    /// Text release tags start with before the version, like the `v` of `v1.2.3`.
+    pub tag_prefix: String,
+}
+
+impl Default for ReleaseSettings {
+    fn default() -> Self {
+        Self { require_bump: false, tag_prefix: "v".to_owned() }
+    }
+}
+
+impl Config {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(CONFIG_FILE);
+        if !std::fs::exists(&path)? { return Ok(Self::default()) }
+        toml::from_str(&std::fs::read_to_string(&path)?).map_err(|e| format!("Invalid {}: {}", CONFIG_FILE, e).into())
+    }
+}
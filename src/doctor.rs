@@ -0,0 +1,318 @@
+use std::path::{Path, PathBuf};
+
+use crate::{app::{App, GRADLE_PROPERTIES}, common::*, config::MappingMode, init::{WRAPPER_PROPERTIES_TEMPLATE, render_template}, jdk::{JAVA_HOME_PROPERTY, find_jdks, jdk_version, pick_jdk}, properties::Properties, wrapper::WRAPPER_JAR};
+
+/// Folders the mod's `fabric.mod.json` can be found in.
+pub const RESOURCE_FOLDERS: [&str; 2] = ["mod_main_resources_folder", "src/main/resources"];
+
+/// Automatic fix for a problem, applied to the project of the given app.
+pub type Fix = Box<dyn Fn(&App) -> Result<()>>;
+
+pub struct Problem {
+    pub description: String,
+    pub advice: String,
+    pub fix: Option<Fix>,
+}
+
+impl Problem {
+    fn new(description: impl Into<String>, advice: impl Into<String>) -> Self {
+        Self { description: description.into(), advice: advice.into(), fix: None }
+    }
+    
+    fn with_fix(mut self, fix: impl Fn(&App) -> Result<()> + 'static) -> Self {
+        self.fix = Some(Box::new(fix));
+        self
+    }
+}
+
+/// Major version of the JDK gradle runs with, from `org.gradle.java.home`, `JAVA_HOME` or the `java` on the path.
+pub fn detect_java_version(properties: &Properties) -> Option<u32> {
+    if let Some(home) = properties.get(JAVA_HOME_PROPERTY) {
+        return jdk_version(Path::new(&home))
+    }
+    let java = match std::env::var_os("JAVA_HOME") {
+        Some(home) => PathBuf::from(home).join("bin").join("java"),
+        None => PathBuf::from("java"),
+    };
+    let output = std::process::Command::new(java).arg("-version").output().ok()?;
+    parse_java_version_output(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Major version from the output of `java -version`, like `openjdk version "21.0.2" 2024-01-16` or `java version "1.8.0_292"`.
+pub fn parse_java_version_output(output: &str) -> Option<u32> {
+    let version = output.split('"').nth(1)?;
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    version.split(['.', '_', '-', '+']).next()?.parse().ok()
+}
+
+/// Replace the value of `"minecraft"` in the `depends` block of a fabric.mod.json with a placeholder.
+fn set_minecraft_dependency(contents: &str, placeholder: &str) -> Option<String> {
+    let depends_start = contents.find("\"depends\"")?;
+    let block_start = depends_start + contents[depends_start..].find('{')? + 1;
+    let block = &contents[block_start..];
+    
+    let Some(key) = block.find("\"minecraft\"") else {
+        return Some(format!("{}\n\t\t\"minecraft\": {},{}", &contents[..block_start], placeholder, block))
+    };
+    let value_start = block_start + key + block[key..].find(':')? + 1;
+    let value = contents[value_start..].trim_start();
+    let value_start = contents.len() - value.len();
+    let value_len = match value.chars().next()? {
+        '[' => value.find(']')? + 1,
+        '"' => value[1..].find('"')? + 2,
+        '$' => value.find('}')? + 1,
+        _ => value.find([',', '\n', '}'])?,
+    };
+    Some(format!("{}{}{}", &contents[..value_start], placeholder, &contents[value_start + value_len..]))
+}
+
+/// Add a flatDir repository for `local_maven` to the top level `repositories` block of a build.gradle.
+/// Blocks nested in others, like the one in `publishing`, are indented, so only an unindented one counts.
+/// Without a top level block, one is added before the `dependencies` block, or at the end.
+pub fn add_flat_dir_repository(contents: &str, local_maven: &str) -> String {
+    let repository = format!("\tflatDir {{\n\t\tdirs \"{}\"\n\t}}\n", local_maven);
+    let line_start = |block: &str| contents.match_indices(block).map(|(index, _)| index).find(|&index| index == 0 || contents.as_bytes()[index - 1] == b'\n');
+    if let Some(index) = line_start("repositories {") {
+        let insert_at = index + contents[index..].find('\n').map_or(contents.len() - index, |i| i + 1);
+        return format!("{}{}{}", &contents[..insert_at], repository, &contents[insert_at..])
+    }
+    let block = format!("repositories {{\n{}}}\n", repository);
+    match line_start("dependencies {") {
+        Some(index) => format!("{}{}\n{}", &contents[..index], block, &contents[index..]),
+        None => format!("{}\n{}", contents, block),
+    }
+}
+
+impl App {
+    pub fn find_fabric_mod_json(&self) -> Option<PathBuf> {
+        RESOURCE_FOLDERS.iter().map(|folder| self.cwd.join(folder).join("fabric.mod.json")).find(|path| path.exists())
+    }
+    
+    /// Name of the placeholder fabric.mod.json uses for the Minecraft dependency.
+    fn range_placeholder(&self) -> String {
+        format!("${{{}}}", self.config.properties.compatible_range)
+    }
+    
+    fn required_properties(&self) -> Vec<&str> {
+        let names = &self.config.properties;
+        let mut required = vec![&names.compatible_range, &names.enforce_range, &names.minecraft_version, &names.java_version, &names.loom_version, &names.loader_version];
+        if self.config.mappings == MappingMode::Yarn {
+            required.push(&names.yarn_mappings);
+        }
+        required.into_iter().map(String::as_str).collect()
+    }
+    
+    fn default_ralli_property(&self, name: &str) -> Result<String> {
+        let names = &self.config.properties;
+        let latest = self.mc_versions.first().ok_or("No Minecraft versions known.");
+        Ok(if name == names.compatible_range {
+            "[]".to_owned()
+        } else if name == names.enforce_range {
+            "false".to_owned()
+        } else if name == names.minecraft_version {
+            latest?.0.to_string()
+        } else if name == names.yarn_mappings {
+            let (version, build) = latest?;
+            format!("{}+build.{}", version, build)
+        } else if name == names.java_version {
+            self.java_version(&latest?.0).to_string()
+        } else if name == names.loom_version {
+            format!("{}-SNAPSHOT", self.fetch_latest_loom()?)
+        } else if name == names.loader_version {
+            self.fetch_latest_loader()?
+        } else {
+            String::new()
+        })
+    }
+    
+    fn check_properties(&self, problems: &mut Vec<Problem>) {
+        let properties = match self.read_properties() {
+            Ok(properties) => properties,
+            Err(e) => {
+                problems.push(Problem::new(format!("Could not read {}: {}", GRADLE_PROPERTIES, e), "Run ralli from the root folder of a Fabric mod project."));
+                return
+            }
+        };
+        
+        if self.find_ralli_marker(&properties).is_none() {
+            problems.push(Problem::new(
+                format!("{} has no ralli marker comment.", GRADLE_PROPERTIES),
+                format!("Add a comment starting with '{}' above the ralli properties and dependencies.", self.config.paths.marker),
+            ).with_fix(|app| {
+                let mut properties = app.read_properties()?;
+                properties.set_comment_at_end(&format!("# {} driven values", app.config.paths.marker));
+                app.write_properties(&properties)
+            }));
+        }
+        
+        for name in self.required_properties() {
+            if properties.get(name).is_none() {
+                let name = name.to_owned();
+                problems.push(Problem::new(
+                    format!("{} is missing the ralli property '{}'.", GRADLE_PROPERTIES, name),
+                    format!("Add '{}=' after the ralli marker comment.", name),
+                ).with_fix(move |app| {
+                    let value = app.default_ralli_property(&name)?;
+                    let mut properties = app.read_properties()?;
+                    app.set_ralli_property(&mut properties, &name, &value);
+                    app.write_properties(&properties)
+                }));
+            }
+        }
+        
+        if let Some(required) = properties.get(&self.config.properties.java_version).and_then(|s| s.trim().parse::<u32>().ok()) {
+            let installed = detect_java_version(&properties);
+            if installed.is_none_or(|installed| installed < required) {
+                let description = match installed {
+                    Some(installed) => format!("Gradle runs on Java {}, but java_version is {}.", installed, required),
+                    None => "No JDK found for gradle to run on.".to_owned(),
+                };
+                let problem = Problem::new(description, format!("Install a JDK {} or newer, ralli selects it when testing a version.", required));
+                problems.push(if pick_jdk(&find_jdks(&self.config.jdk.search_paths), required).is_some() {
+                    problem.with_fix(move |app| {
+                        let mut properties = app.read_properties()?;
+                        app.select_jdk(&mut properties, required);
+                        app.write_properties(&properties)
+                    })
+                } else { problem });
+            }
+        }
+    }
+    
+    fn check_gradle_wrapper(&self, problems: &mut Vec<Problem>) {
+        let path = self.cwd.join(&self.config.paths.gradle_wrapper_properties);
+        let has_url = std::fs::read_to_string(&path).ok().and_then(|s| Properties::parse(&s).get("distributionUrl")).is_some_and(|url| !url.is_empty());
+        if !has_url {
+            problems.push(Problem::new(
+                format!("{} is missing or has no distributionUrl.", self.config.paths.gradle_wrapper_properties),
+                "Set up the gradle wrapper with 'gradle wrapper'.",
+            ).with_fix(|app| {
+                let path = app.cwd.join(&app.config.paths.gradle_wrapper_properties);
+                if !path.exists() {
+                    std::fs::create_dir_all(path.parent().ok_or("Invalid gradle wrapper path.")?)?;
+                    write_atomic(&path, render_template(WRAPPER_PROPERTIES_TEMPLATE, &[("gradle_url", ""), ("gradle_sha256", "")]))?;
+                }
+                let mut properties = Properties::parse(&std::fs::read_to_string(&path)?);
+                if properties.get("distributionUrl").is_none() {
+                    properties.set("distributionUrl", "");
+                    write_atomic(&path, properties.to_string())?;
+                }
+                app.update_gradle()
+            }));
+            return
+        }
+        
+        let has_checksum = std::fs::read_to_string(&path).ok().and_then(|s| Properties::parse(&s).get("distributionSha256Sum")).is_some_and(|sum| !sum.is_empty());
+        if !has_checksum {
+            problems.push(Problem::new(
+                format!("{} has no distributionSha256Sum, so the downloaded gradle distribution isn't verified.", self.config.paths.gradle_wrapper_properties),
+                "Add the published checksum of the distribution as distributionSha256Sum.",
+            ).with_fix(App::pin_distribution_checksum));
+        }
+        match self.verify_wrapper_jar() {
+            Ok(None) => (),
+            Ok(Some(mismatch)) => problems.push(Problem::new(
+                format!("The gradle wrapper jar is not the official one. {}", mismatch),
+                "Regenerate the wrapper with 'gradle wrapper' from a gradle install you trust.",
+            )),
+            Err(e) => problems.push(Problem::new(
+                format!("Could not verify the gradle wrapper jar: {}", e),
+                format!("Make sure {} exists next to the wrapper properties.", WRAPPER_JAR),
+            )),
+        }
+    }
+    
+    fn check_build_gradle(&self, problems: &mut Vec<Problem>) {
+        let contents = match std::fs::read_to_string(self.cwd.join("build.gradle")) {
+            Ok(contents) => contents,
+            Err(e) => {
+                problems.push(Problem::new(format!("Could not read build.gradle: {}", e), "ralli only supports projects with a groovy build.gradle."));
+                return
+            }
+        };
+        let local_maven = &self.config.paths.local_maven;
+        if !(contents.contains("flatDir") && contents.contains(local_maven.as_str())) {
+            problems.push(Problem::new(
+                format!("build.gradle does not use the '{}' flatDir repository, so downloaded dependencies won't be found.", local_maven),
+                format!("Add 'flatDir {{ dirs \"{}\" }}' to the repositories block.", local_maven),
+            ).with_fix(|app| {
+                let path = app.cwd.join("build.gradle");
+                let contents = std::fs::read_to_string(&path)?;
+                let new_contents = add_flat_dir_repository(&contents, &app.config.paths.local_maven);
+                write_atomic(path, new_contents)
+            }));
+        }
+    }
+    
+    fn check_fabric_mod_json(&self, problems: &mut Vec<Problem>) {
+        let Some(path) = self.find_fabric_mod_json() else {
+            problems.push(Problem::new(
+                format!("No fabric.mod.json found in {}.", RESOURCE_FOLDERS.join(" or ")),
+                "Add a fabric.mod.json to the mod's resources.",
+            ));
+            return
+        };
+        let placeholder = self.range_placeholder();
+        if !std::fs::read_to_string(&path).is_ok_and(|contents| contents.contains(&placeholder)) {
+            problems.push(Problem::new(
+                format!("{} does not use {} for its Minecraft dependency.", path.display(), placeholder),
+                format!("Set \"minecraft\": {} in the depends block.", placeholder),
+            ).with_fix(move |app| {
+                let contents = std::fs::read_to_string(&path)?;
+                let new_contents = set_minecraft_dependency(&contents, &app.range_placeholder()).ok_or("Could not find the depends block in fabric.mod.json.")?;
+                write_atomic(&path, new_contents)
+            }));
+        }
+    }
+    
+    /// Gradle can't run through the wrapper script without the wrapper jar, which 'ralli init' can't create.
+    fn check_wrapper_jar(&self) -> Option<Problem> {
+        let uses_wrapper = Path::new(&self.config.paths.gradle).file_stem().is_some_and(|name| name == "gradlew");
+        // Outside of a project the missing gradle properties are the problem to report
+        if !uses_wrapper || !self.cwd.join(GRADLE_PROPERTIES).exists() || self.wrapper_jar_path().exists() { return None }
+        Some(Problem::new(
+            format!("{} is missing, so gradle can't run. Other problems are checked once it's there.", WRAPPER_JAR),
+            "Run 'gradle wrapper' with an installed gradle to add it.",
+        ))
+    }
+    
+    pub fn diagnose(&self) -> Vec<Problem> {
+        // Some fixes run gradle, so nothing else can be fixed before the wrapper works
+        if let Some(problem) = self.check_wrapper_jar() { return vec![problem] }
+        let mut problems = vec![];
+        self.check_properties(&mut problems);
+        self.check_gradle_wrapper(&mut problems);
+        self.check_build_gradle(&mut problems);
+        self.check_fabric_mod_json(&mut problems);
+        problems
+    }
+    
+    /// Check the project setup and print a report, applying the available fixes if `fix` is set.
+    pub fn doctor(&self, fix: bool) -> Result<()> {
+        let problems = self.diagnose();
+        if problems.is_empty() {
+            println!("No problems found.");
+            return Ok(())
+        }
+        
+        let mut fixable = 0;
+        for problem in &problems {
+            println!("Problem: {}", problem.description);
+            println!("    {}", problem.advice);
+            if let Some(apply_fix) = &problem.fix {
+                if fix {
+                    match apply_fix(self) {
+                        Ok(()) => println!("    Fixed."),
+                        Err(e) => println!("    Could not fix automatically: {}", e),
+                    }
+                } else {
+                    fixable += 1;
+                }
+            }
+        }
+        if fixable > 0 {
+            println!("{} of {} problems can be fixed automatically with 'doctor fix'.", fixable, problems.len());
+        }
+        Ok(())
+    }
+}
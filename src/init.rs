@@ -0,0 +1,137 @@
+use crate::{app::{App, GRADLE_PROPERTIES}, common::*};
+
+pub const WRAPPER_PROPERTIES_TEMPLATE: &str = include_str!("../templates/gradle-wrapper.properties");
+
+/// build.gradle of the example project, which new projects share apart from the example's own dependencies.
+const EXAMPLE_BUILD_GRADLE: &str = include_str!("../build.gradle");
+
+/// Comment in the example build.gradle above its own dependencies, which run to the end of the `dependencies` block.
+const EXAMPLE_DEPENDENCIES_COMMENT: &str = "\t// Dependencies of the example mod";
+
+/// Files of a new project, as paths relative to the project folder (which can contain placeholders) and their templates.
+const TEXT_TEMPLATES: [(&str, &str); 10] = [
+    ("settings.gradle", include_str!("../templates/settings.gradle")),
+    (GRADLE_PROPERTIES, include_str!("../templates/gradle.properties")),
+    ("gradle/wrapper/gradle-wrapper.properties", WRAPPER_PROPERTIES_TEMPLATE),
+    ("mod_main_resources_folder/fabric.mod.json", include_str!("../templates/fabric.mod.json")),
+    ("mod_main_resources_folder/{{modid}}.mixins.json", include_str!("../templates/mixins.json")),
+    ("mod_main_resources_folder/{{modid}}.client.mixins.json", include_str!("../templates/client.mixins.json")),
+    ("mod_main_java_folder/{{package_path}}/{{class_name}}.java", include_str!("../templates/Mod.java")),
+    ("mod_main_java_folder/{{package_path}}/client/{{class_name}}Client.java", include_str!("../templates/ModClient.java")),
+    ("mod_main_java_folder/{{package_path}}/mixin/ExampleMixin.java", include_str!("../templates/ExampleMixin.java")),
+    ("mod_main_java_folder/{{package_path}}/client/mixin/ExampleClientMixin.java", include_str!("../templates/ExampleClientMixin.java")),
+];
+
+const BINARY_TEMPLATES: [(&str, &[u8]); 3] = [
+    ("gradlew", include_bytes!("../gradlew")),
+    ("gradlew.bat", include_bytes!("../gradlew.bat")),
+    ("mod_main_resources_folder/assets/{{modid}}/icon.png", include_bytes!("../mod_main_resources_folder/assets/modid/icon.png")),
+];
+
+
+/// Replace every `{{name}}` in a template with its value.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut result = template.to_owned();
+    for (name, value) in values {
+        result = result.replace(&format!("{{{{{}}}}}", name), value);
+    }
+    result
+}
+
+/// Template for the build.gradle of a new project, made from the example project's one.
+pub fn build_gradle_template(example: &str) -> String {
+    let template = example.replace("\"com/example/", "\"{{package_path}}/");
+    let Some(start) = template.find(EXAMPLE_DEPENDENCIES_COMMENT) else { return template };
+    let end = start + template[start..].find("\n}").unwrap_or(template.len() - start);
+    format!("{}{}", template[..start].trim_end(), &template[end..])
+}
+
+/// Mod ids as allowed by https://wiki.fabricmc.net/documentation:fabric_mod_json_spec#id
+pub fn is_valid_mod_id(mod_id: &str) -> bool {
+    (2..=64).contains(&mod_id.len())
+        && mod_id.starts_with(|c: char| c.is_ascii_lowercase())
+        && mod_id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+pub fn is_valid_package(package: &str) -> bool {
+    package.split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Java class name for a mod id, like `MyCoolMod` for `my-cool_mod`.
+pub fn class_name(mod_id: &str) -> String {
+    mod_id.split(['-', '_']).map(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+            None => String::new(),
+        }
+    }).collect()
+}
+
+impl App {
+    /// Create a new Fabric mod project in a folder named after the mod id, set up for the latest Minecraft version.
+    pub fn init_project(&self, mod_id: &str, package: &str) -> Result<()> {
+        if !is_valid_mod_id(mod_id) {
+            return Err(format!("'{}' is not a valid mod id. Use 2 to 64 lowercase letters, digits, '-' or '_', starting with a letter.", mod_id).into())
+        }
+        if !is_valid_package(package) {
+            return Err(format!("'{}' is not a valid java package name.", package).into())
+        }
+        let project_dir = self.cwd.join(mod_id);
+        if std::fs::exists(&project_dir)? && std::fs::read_dir(&project_dir)?.next().is_some() {
+            return Err(format!("Folder {} already exists and is not empty.", project_dir.display()).into())
+        }
+        
+        let (minecraft_version, yarn_build) = self.mc_versions.first().ok_or("No Minecraft versions known.")?;
+        let loom_version = format!("{}-SNAPSHOT", self.fetch_latest_loom()?);
+        let loader_version = self.fetch_latest_loader()?;
+        let fabric_api_version = self.find_fabric_api(minecraft_version)?.ok_or(format!("No Fabric API build found for Minecraft {}.", minecraft_version))?.version_number;
+        let gradle = self.fetch_latest_gradle()?;
+        let gradle_sha256 = self.api_request_text(&gradle.checksumUrl)?;
+        
+        let minecraft_version = minecraft_version.to_string();
+        let yarn_mappings = format!("{}+build.{}", minecraft_version, yarn_build);
+        let java_version = self.java_version(&self.mc_versions[0].0).to_string();
+        let class_name = class_name(mod_id);
+        let package_path = package.replace('.', "/");
+        let values = [
+            ("modid", mod_id),
+            ("package", package),
+            ("package_path", &package_path),
+            ("class_name", &class_name),
+            ("minecraft_version", &minecraft_version),
+            ("yarn_mappings", &yarn_mappings),
+            ("java_version", &java_version),
+            ("loom_version", &loom_version),
+            ("loader_version", &loader_version),
+            ("fabric_api_version", &fabric_api_version),
+            ("gradle_url", &gradle.downloadUrl),
+            ("gradle_sha256", &gradle_sha256),
+        ];
+        
+        let write_file = |path: &str, contents: &[u8]| -> Result<()> {
+            let path = project_dir.join(render_template(path, &values));
+            std::fs::create_dir_all(path.parent().ok_or("Invalid template path.")?)?;
+            write_atomic(path, contents)
+        };
+        write_file("build.gradle", render_template(&build_gradle_template(EXAMPLE_BUILD_GRADLE), &values).as_bytes())?;
+        for (path, template) in TEXT_TEMPLATES {
+            write_file(path, render_template(template, &values).as_bytes())?;
+        }
+        for (path, contents) in BINARY_TEMPLATES {
+            write_file(path, contents)?;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(project_dir.join("gradlew"), std::fs::Permissions::from_mode(0o755))?;
+        }
+        
+        println!("Created mod '{}' for Minecraft {} in {}.", mod_id, minecraft_version, project_dir.display());
+        println!("Run 'gradle wrapper' in the new folder to add gradle-wrapper.jar, then start ralli there.");
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use crate::{app::App, config::JdkSelection, doctor::parse_java_version_output, properties::Properties};
+
+/// Gradle property pointing gradle at the JDK to run with.
+pub const JAVA_HOME_PROPERTY: &str = "org.gradle.java.home";
+/// Gradle property listing extra JDKs for toolchain resolution.
+pub const TOOLCHAIN_PATHS_PROPERTY: &str = "org.gradle.java.installations.paths";
+
+/// Folders JDKs are commonly installed into, each holding one JDK per subfolder. `~` is the user's home folder.
+pub const JDK_FOLDERS: [&str; 12] = [
+    "/usr/lib/jvm",
+    "/usr/java",
+    "/usr/local/java",
+    "/opt/java",
+    "/opt/jdk",
+    "/Library/Java/JavaVirtualMachines",
+    "C:/Program Files/Java",
+    "C:/Program Files/Eclipse Adoptium",
+    "C:/Program Files/Microsoft",
+    "~/.sdkman/candidates/java",
+    "~/.gradle/jdks",
+    "~/.jdks",
+];
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jdk {
+    pub home: PathBuf,
+    pub major_version: u32,
+}
+
+fn expand_home(folder: &str) -> Option<PathBuf> {
+    match folder.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME").or(std::env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(rest)),
+        None => Some(PathBuf::from(folder)),
+    }
+}
+
+/// Major version of the JDK in a folder, from its `release` file or else by running its `java -version`.
+pub fn jdk_version(home: &Path) -> Option<u32> {
+    let java = home.join("bin").join(if cfg!(windows) { "java.exe" } else { "java" });
+    if !java.exists() { return None }
+    
+    if let Ok(release) = std::fs::read_to_string(home.join("release"))
+        && let Some(line) = release.lines().find(|line| line.starts_with("JAVA_VERSION="))
+        && let Some(version) = parse_java_version_output(line) {
+        return Some(version)
+    }
+    let output = std::process::Command::new(java).arg("-version").output().ok()?;
+    parse_java_version_output(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Find the JDKs installed in `JAVA_HOME`, the common install folders and the given extra folders.
+pub fn find_jdks(extra_folders: &[String]) -> Vec<Jdk> {
+    let mut homes = vec![];
+    homes.extend(std::env::var_os("JAVA_HOME").map(PathBuf::from));
+    
+    let folders = JDK_FOLDERS.iter().copied().chain(extra_folders.iter().map(String::as_str)).filter_map(expand_home);
+    for folder in folders {
+        // A search path can point at a JDK itself as well as at a folder of JDKs
+        homes.push(folder.clone());
+        let Ok(entries) = std::fs::read_dir(&folder) else { continue };
+        for entry in entries.flatten() {
+            let home = entry.path();
+            // macOS bundles keep the JDK in Contents/Home
+            let bundle_home = home.join("Contents").join("Home");
+            homes.push(if bundle_home.exists() { bundle_home } else { home });
+        }
+    }
+    
+    let mut jdks: Vec<Jdk> = vec![];
+    for home in homes {
+        let home = home.canonicalize().unwrap_or(home);
+        if jdks.iter().any(|jdk| jdk.home == home) { continue }
+        if let Some(major_version) = jdk_version(&home) {
+            jdks.push(Jdk { home, major_version });
+        }
+    }
+    jdks
+}
+
+/// JDK to build a Minecraft version needing Java `required` with: the same major version if installed, else the oldest newer one.
+pub fn pick_jdk(jdks: &[Jdk], required: u32) -> Option<&Jdk> {
+    jdks.iter().filter(|jdk| jdk.major_version >= required).min_by_key(|jdk| jdk.major_version)
+}
+
+impl App {
+    /// Point gradle at an installed JDK for Java `required`, warning if there is none.
+    pub fn select_jdk(&self, properties: &mut Properties, required: u32) {
+        if self.config.jdk.selection == JdkSelection::Off { return }
+        
+        let jdks = find_jdks(&self.config.jdk.search_paths);
+        let Some(jdk) = pick_jdk(&jdks, required) else {
+            println!("WARNING: No JDK {} or newer found. Install one or add its folder to jdk.search_paths in ralli.toml.", required);
+            return
+        };
+        let home = |jdk: &Jdk| jdk.home.to_string_lossy().replace('\\', "/");
+        let (property, value) = match self.config.jdk.selection {
+            // Let gradle pick from every installed JDK for the toolchain build.gradle asks for
+            JdkSelection::Toolchain => (TOOLCHAIN_PATHS_PROPERTY, jdks.iter().map(home).collect::<Vec<_>>().join(",")),
+            _ => (JAVA_HOME_PROPERTY, home(jdk)),
+        };
+        if properties.get(property).is_some_and(|current| current == value) { return }
+        
+        println!("Using JDK {} at {}.", jdk.major_version, home(jdk));
+        // Keep gradle settings together with the other org.gradle properties at the top
+        match properties.entries().into_iter().rev().find(|entry| entry.key.starts_with("org.gradle.")) {
+            Some(last) => properties.set_or_insert_after(last.line + last.line_count - 1, property, &value),
+            None => properties.set(property, &value),
+        }
+    }
+}
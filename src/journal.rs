@@ -0,0 +1,52 @@
+use std::{ffi::OsString, path::PathBuf};
+
+/// State of the files ralli commands change, as it was before or after an operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub properties: Option<String>,
+    pub lockfile: Option<String>,
+    /// Names of the jars in `run/mods`, which are all copies of jars in the local maven folder.
+    pub mods: Vec<OsString>,
+    /// Contents of the other project files commands edit, like build.gradle and the gradle wrapper files, `None` where missing.
+    pub files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+#[derive(Debug)]
+pub struct Operation {
+    pub name: String,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+/// Reversible operations done during this session.
+#[derive(Default, Debug)]
+pub struct Journal {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl Journal {
+    pub fn record(&mut self, operation: Operation) {
+        if operation.before == operation.after { return }
+        self.undo.push(operation);
+        self.redo.clear();
+    }
+    
+    pub fn next_undo(&self) -> Option<&Operation> {
+        self.undo.last()
+    }
+    
+    pub fn next_redo(&self) -> Option<&Operation> {
+        self.redo.last()
+    }
+    
+    /// Move the operation returned by `next_undo` onto the redo stack once it has been undone.
+    pub fn finish_undo(&mut self) {
+        if let Some(operation) = self.undo.pop() { self.redo.push(operation) }
+    }
+    
+    /// Move the operation returned by `next_redo` back onto the undo stack once it has been redone.
+    pub fn finish_redo(&mut self) {
+        if let Some(operation) = self.redo.pop() { self.undo.push(operation) }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+
+pub const LOCKFILE: &str = "ralli.lock";
+
+
+/// Record of the dependency versions resolved by the last `fetch_dependencies` run, and of the pinned yarn builds.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Lockfile {
+    pub minecraft_version: String,
+    pub dependencies: Vec<LockedDependency>,
+    /// Optional dependencies that were left out because they do not support `minecraft_version`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+    /// Yarn build to use for a Minecraft version instead of the latest one.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub yarn_pins: BTreeMap<String, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LockedDependency {
+    pub name: String,
+    pub version: String,
+    /// Minecraft version the dependency was resolved for, if it had to fall back from `minecraft_version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+    /// Oldest confirmed Minecraft version when it is older than `minecraft_version`, with the dependency version resolved for it.
+    /// The lower of the two versions is the minimum written to fabric.mod.json, and isn't resolved again while this stays the same.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_confirmed: Option<(String, String)>,
+}
+
+impl Lockfile {
+    /// Read the lockfile of a project, or an empty one if it has none yet.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(LOCKFILE);
+        if !std::fs::exists(&path)? { return Ok(Self::default()) }
+        serde_json::from_str(&std::fs::read_to_string(&path)?).map_err(|e| format!("Invalid {}: {}", LOCKFILE, e).into())
+    }
+    
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<()> {
+        write_atomic(dir.as_ref().join(LOCKFILE), serde_json::to_string_pretty(self)? + "\n")
+    }
+}
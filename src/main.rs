@@ -4,14 +4,37 @@ pub mod common;
 pub mod api_structs;
 pub mod semantic_version;
 pub mod app;
+pub mod bump;
+pub mod config;
+pub mod doctor;
+pub mod init;
+pub mod jdk;
+pub mod journal;
+pub mod lockfile;
+pub mod minecraft_version;
+pub mod properties;
+pub mod ranges;
+pub mod state;
+pub mod toolchain;
+pub mod update;
+pub mod workspace;
+pub mod wrapper;
 
 #[cfg(test)] mod test;
 
 use common::*;
-use crate::{app::{App, GRADLE}, semantic_version::SemanticVersion};
+use crate::{app::App, update::Tool, workspace::{TestOutcome, print_table}};
 
 
-fn handle_command(app: &App, line: &str) -> Result<bool> {
+/// Ask a yes or no question on the console, defaulting to no.
+fn ask(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok_and(|_| matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn handle_command(app: &mut App, line: &str) -> Result<bool> {
     let mut parts = line.split_whitespace();
     Ok(if let Some(first) = parts.next() {
         match first.to_lowercase().as_str() {
@@ -21,13 +44,13 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
                 false
             }
             "stop" => {
-                run_command(GRADLE, ["--stop"])?;
+                app.run_gradle(["--stop"])?;
                 false
             }
             "clean" => {
                 if let Some(next) = parts.next() {
                     match next.to_lowercase().as_str() {
-                        "gradle" => run_command(GRADLE, ["clean", "--no-build-cache", "--refresh-dependencies"])?,
+                        "gradle" => app.run_gradle(["clean", "--no-build-cache", "--refresh-dependencies"])?,
                         "deps" => app.clean_dependencies()?,
                         _ => println!("Usage: clean (gradle | deps)")
                     }
@@ -35,11 +58,11 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
                 false
             }
             "build" => {
-                run_command(GRADLE, ["clean", "build"])?;
+                app.run_gradle(["clean", "build"])?;
                 false
             }
             "gradle" => {
-                run_command(GRADLE, parts)?;
+                app.run_gradle(parts)?;
                 false
             }
             "git" => {
@@ -48,12 +71,9 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
             }
             "test" => {
                 if let Some(s) = parts.next() {
-                    match s.parse::<SemanticVersion>() {
-                        Ok(version) => match app.mc_versions.iter().position(|(v, _)| *v == version) {
-                            Some(index) => app.test_version(index)?,
-                            None => println!("Minecraft version {} not found.", version)
-                        }
-                        Err(_) => println!("'{}' isn't a version!", s)
+                    match app.version_index(s) {
+                        Ok(index) => app.record(line.trim(), |app| app.test_version(index))?,
+                        Err(e) => println!("{}", e),
                     }
                 } else {
                     println!("Usage: test <version>");
@@ -61,15 +81,11 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
                 false
             }
             "up" => {
+                // mc_versions is sorted newest first
                 match app.get_current_ranges() {
-                    Ok(ranges) => match ranges.last() {
-                        Some(last) => match &last.end {
-                            Some(end) => match app.mc_versions.iter().position(|(v, _)| v.matches_numbers(end)) {
-                                Some(index) => app.test_version(index)?,
-                                None => println!("Minecraft version {} not found.", end)
-                            }
-                            None => println!("No available Minecraft versions later than {}.", app.mc_versions.first().unwrap().0)
-                        }
+                    Ok(ranges) => match app.mc_versions.iter().position(|(v, _)| ranges.contains(&v.semver)) {
+                        Some(0) => println!("No available Minecraft versions later than {}.", app.mc_versions[0].0),
+                        Some(index) => app.record("up", |app| app.test_version(index - 1))?,
                         None => println!("No known compatible versions yet. Use 'test <version>' instead.")
                     }
                     Err(e) => println!("Could not get known compatible versions: {e}")
@@ -78,18 +94,9 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
             }
             "down" => {
                 match app.get_current_ranges() {
-                    Ok(ranges) => match ranges.first() {
-                        Some(first) => match &first.start {
-                            Some(start) => match app.mc_versions.iter().position(|(v, _)| v.matches_numbers(start)) {
-                                Some(index) => if index + 1 >= app.mc_versions.len() {
-                                    println!("No available Minecraft versions earlier than {}.", app.mc_versions.last().unwrap().0)
-                                } else {
-                                    app.test_version(index + 1)?
-                                }
-                                None => println!("Minecraft version {} not found.", start)
-                            }
-                            None => println!("No available Minecraft versions earlier than {}.", app.mc_versions.last().unwrap().0)
-                        }
+                    Ok(ranges) => match app.mc_versions.iter().rposition(|(v, _)| ranges.contains(&v.semver)) {
+                        Some(index) if index + 1 >= app.mc_versions.len() => println!("No available Minecraft versions earlier than {}.", app.mc_versions[index].0),
+                        Some(index) => app.record("down", |app| app.test_version(index + 1))?,
                         None => println!("No known compatible versions yet. Use 'test <version>' instead.")
                     }
                     Err(e) => println!("Could not get known compatible versions: {e}")
@@ -97,15 +104,126 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
                 false
             }
             "deps" => {
-                app.fetch_dependencies()?;
+                app.record("deps", App::fetch_dependencies)?;
                 false
             }
             "confirm" => {
-                app.confirm_version()?;
+                app.record("confirm", App::confirm_version)?;
+                false
+            }
+            "bump" => {
+                match line.trim()[first.len()..].parse::<bump::Bump>() {
+                    Ok(bump) => app.record(line.trim(), |app| app.bump_mod_version(&bump))?,
+                    Err(e) => println!("{} Usage: bump major|minor|patch|pre <tag>", e),
+                }
                 false
             }
             "release" => {
-                app.release()?;
+                app.record("release", App::release)?;
+                false
+            }
+            "doctor" => {
+                match parts.next() {
+                    Some("fix") => app.record("doctor fix", |app| app.doctor(true))?,
+                    _ => app.doctor(false)?,
+                }
+                false
+            }
+            "init" => {
+                match (parts.next(), parts.next()) {
+                    (Some(mod_id), Some(package)) => app.init_project(mod_id, package)?,
+                    _ => println!("Usage: init <modid> <package>"),
+                }
+                false
+            }
+            "status" => {
+                app.status()?;
+                false
+            }
+            "sweep" => {
+                let (oldest, newest) = app.sweep_bounds(parts.next(), parts.next())?;
+                let mut results = vec![];
+                app.record("sweep", |app| { results = app.sweep(oldest, newest)?; Ok(()) })?;
+                let rows = results.into_iter().map(|(version, outcome)| vec![version.to_string(), match outcome {
+                    TestOutcome::Unsupported(reason) => format!("unsupported: {}", reason),
+                    outcome => outcome.to_string(),
+                }]).collect::<Vec<_>>();
+                print_table(&["minecraft".to_owned(), "result".to_owned()], &rows);
+                false
+            }
+            "workspace" => {
+                app.run_workspace_command(&parts.collect::<Vec<_>>())?;
+                false
+            }
+            "update" => {
+                let tools = match parts.next() {
+                    Some(s) => vec![s.parse::<Tool>()?],
+                    None => Tool::ALL.to_vec(),
+                };
+                let updates = app.plan_updates(&tools);
+                if !updates.is_empty() {
+                    for update in &updates {
+                        println!("    {}", update);
+                    }
+                    if updates.iter().all(|update| update.blocked.is_some()) {
+                        println!("No updates can be applied.");
+                    } else if ask("Apply these updates?") {
+                        app.record("update", |app| {
+                            app.apply_updates(&updates);
+                            Ok(())
+                        })?;
+                    }
+                }
+                false
+            }
+            "yarn" => {
+                match (parts.next(), parts.next()) {
+                    (None, _) => {
+                        let version = app.find_property(&app.read_properties()?, &app.config.properties.minecraft_version)?;
+                        app.list_yarn_builds(app.version_index(&version)?)?
+                    }
+                    (Some("pin"), Some(build)) => match build.parse::<u32>() {
+                        Ok(build) => app.record(line.trim(), |app| app.pin_yarn(Some(build)))?,
+                        Err(_) => println!("'{}' isn't a yarn build number!", build),
+                    }
+                    (Some("unpin"), None) => app.record("yarn unpin", |app| app.pin_yarn(None))?,
+                    (Some(version), None) if version != "pin" => app.list_yarn_builds(app.version_index(version)?)?,
+                    _ => println!("Usage: yarn [<version> | pin <build> | unpin]"),
+                }
+                false
+            }
+            "range" => {
+                let expression = line.trim()[first.len()..].trim();
+                if expression.is_empty() {
+                    println!("Usage: range <expression>, like 'range confirmed minus dep:fabric-api' or 'range \">=1.19 <1.21\" minus confirmed'");
+                } else {
+                    app.print_range(expression)?;
+                }
+                false
+            }
+            "undo" => {
+                app.undo()?;
+                false
+            }
+            "redo" => {
+                app.redo()?;
+                false
+            }
+            "restore" => {
+                let backups = app.list_backups()?;
+                match parts.next() {
+                    Some(s) => match s.parse::<usize>().ok().and_then(|n| backups.get(n.wrapping_sub(1))) {
+                        Some(backup) => app.record(line.trim(), |app| app.restore_backup(backup))?,
+                        None => println!("No backup number '{s}'. Use 'restore' to list available backups."),
+                    }
+                    None if backups.is_empty() => println!("No gradle properties backups yet."),
+                    None => {
+                        for (i, backup) in backups.iter().enumerate() {
+                            println!("{:>3}: {}", i + 1, backup.file_name().unwrap_or_default().to_string_lossy());
+                        }
+                        println!("Use 'restore <number>' to restore one of these backups.");
+                    }
+                }
                 false
             }
             s => {
@@ -117,26 +235,45 @@ fn handle_command(app: &App, line: &str) -> Result<bool> {
 }
 
 fn main() {
+    let mut app = match App::new() {
+        Ok(app) => app,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1)
+        }
+    };
+    if let Err(e) = app.load_config() {
+        println!("{} Using the default settings.", e);
+    }
     
-    let mut app = App::new();
-    println!("Stopping gradle daemons...");
-    run_command(GRADLE, ["--stop"]).unwrap();
-    app.update_gradle().unwrap();
-    app.update_static_info().unwrap();
-    app.fetch_version_info().unwrap();
+    // Tool updates only happen at startup when turned on in ralli.toml, otherwise use the 'update' command
+    let startup = &app.config.startup;
+    if startup.stop_daemons {
+        println!("Stopping gradle daemons...");
+        if let Err(e) = app.run_gradle(["--stop"]) { println!("Could not stop gradle daemons: {}", e); }
+    }
+    let tools = Tool::ALL.into_iter().zip([startup.gradle, startup.loom, startup.loader]).filter_map(|(tool, enabled)| enabled.then_some(tool)).collect::<Vec<_>>();
+    if !tools.is_empty() {
+        let updates = app.plan_updates(&tools);
+        app.apply_updates(&updates);
+    }
+    
+    if let Err(e) = app.fetch_version_info() {
+        println!("Could not get the Minecraft version list: {}", e);
+    }
     
-    match app.mc_versions.first().map(|(first, _)| app.mc_versions.last().map(|(last, _)| (first.clone(), last.clone()))).flatten() {
+    match app.mc_versions.first().and_then(|(first, _)| app.mc_versions.last().map(|(last, _)| (first.clone(), last.clone()))) {
         Some((first, last)) => println!("Found {} Minecraft versions from {} to {}", app.mc_versions.len(), last, first),
         None => println!("No Minecraft versions found.")
     }
     
     loop {
-        print!("[ralli] {}> ", app.cwd.file_name().map(|s| s.to_str()).flatten().unwrap_or("?"));
-        std::io::stdout().flush().unwrap();
+        print!("[ralli] {}> ", app.cwd.file_name().and_then(|s| s.to_str()).unwrap_or("?"));
+        let _ = std::io::stdout().flush();
         
         let mut line = String::new();
-        std::io::stdin().read_line(&mut line).unwrap();
-        if let Ok(true) = handle_command(&app, &line).inspect_err(|e| println!("{e}")) { break }
+        if let Ok(0) | Err(_) = std::io::stdin().read_line(&mut line) { break }
+        if let Ok(true) = handle_command(&mut app, &line).inspect_err(|e| println!("{e}")) { break }
     }
-    
+
 }
@@ -0,0 +1,159 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{api_structs::{VersionDetails, VersionManifest}, app::{App, get_java_version}, common::*, semantic_version::SemanticVersion};
+
+
+/// Minecraft version as Mojang names it, like `1.21.4`, `1.21-pre1`, `1.21-rc1` or `24w14a`.
+/// Versions are ordered by their release time in Mojang's version manifest, with versions missing from it after the rest by their normalized semver.
+#[derive(Debug, Clone)]
+pub struct MinecraftVersion {
+    pub id: String,
+    /// The version as the semver Fabric Loader matches version ranges against, like `1.21-beta.1` for `1.21-pre1`.
+    pub semver: SemanticVersion,
+    /// Release time from Mojang's version manifest, like `2024-12-03T10:12:57+00:00`.
+    pub release_time: Option<String>,
+}
+
+/// Normalize a Minecraft version id to semver the way Fabric Loader does.
+/// Weekly snapshots like `24w14a` become prereleases of the release they lead up to, `next_release`.
+pub fn normalize_minecraft_version(id: &str, next_release: Option<&str>) -> Result<SemanticVersion> {
+    let invalid = || format!("'{}' isn't a Minecraft version.", id);
+    let release = |s: &str| -> Result<SemanticVersion> {
+        if s.is_empty() || !s.split('.').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit())) { return Err(invalid().into()) }
+        s.parse()
+    };
+    let numbered = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+    
+    // Weekly snapshots, like 24w14a
+    if let Some((year, rest)) = id.split_once('w') && year.len() == 2 && rest.len() == 3 && rest.is_ascii() && let (Ok(year), Ok(week)) = (year.parse::<u32>(), rest[..2].parse::<u32>()) {
+        let letter = &rest[2..];
+        if !letter.bytes().all(|b| b.is_ascii_lowercase()) { return Err(invalid().into()) }
+        let next_release = next_release.ok_or(format!("Snapshot {} has no known release to normalize against.", id))?;
+        let mut version = release(next_release)?;
+        version.release = Some(format!("alpha.{}.{}.{}", year, week, letter));
+        return Ok(version)
+    }
+    
+    let (base, suffix) = match id.split_once('-') {
+        Some((base, suffix)) => (base, Some(suffix)),
+        None => (id, None),
+    };
+    let mut version = release(base)?;
+    version.release = match suffix {
+        None => None,
+        Some(suffix) => Some(if let Some(n) = suffix.strip_prefix("pre") {
+            format!("beta.{}", numbered(n)?)
+        } else if let Some(n) = suffix.strip_prefix("rc") {
+            format!("rc.{}", numbered(n)?)
+        } else if let Some(n) = suffix.strip_prefix("snapshot-") {
+            format!("alpha.{}", numbered(n)?)
+        } else {
+            return Err(invalid().into())
+        }),
+    };
+    Ok(version)
+}
+
+impl MinecraftVersion {
+    pub fn new(id: &str, next_release: Option<&str>) -> Result<Self> {
+        Ok(Self { id: id.to_owned(), semver: normalize_minecraft_version(id, next_release)?, release_time: None })
+    }
+    
+    /// Whether this is a full release, not a snapshot, pre-release or release candidate.
+    pub fn is_release(&self) -> bool {
+        self.semver.release.is_none()
+    }
+}
+
+impl std::fmt::Display for MinecraftVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl std::str::FromStr for MinecraftVersion {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s.trim(), None)
+    }
+}
+
+/// Versions are the same if their normalized semver is, so `1.21` and `1.21.0` are equal.
+impl PartialEq for MinecraftVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.semver == other.semver
+    }
+}
+
+impl Eq for MinecraftVersion {}
+
+impl Ord for MinecraftVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.semver == other.semver { return Ordering::Equal }
+        // Versions without a release time aren't in the cached manifest yet, so they are newer than every version in it
+        let key = |version: &Self| (version.release_time.is_none(), version.release_time.clone());
+        key(self).cmp(&key(other)).then_with(|| self.semver.cmp(&other.semver))
+    }
+}
+
+impl PartialOrd for MinecraftVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+serde_as_string!(MinecraftVersion);
+
+
+/// What ralli keeps of a version from Mojang's version manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestInfo {
+    pub release_time: String,
+    /// Major Java version the version needs, from the `javaVersion` of its version json.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java_version: Option<u32>,
+}
+
+impl App {
+    /// Release time and Java version of each Minecraft version, by version id, from Mojang's version manifest.
+    /// Versions already in the cache file aren't fetched again.
+    pub fn fetch_manifest_info(&self, versions: &[MinecraftVersion]) -> Result<BTreeMap<String, ManifestInfo>> {
+        let cache_path = self.cwd.join(&self.config.paths.version_cache);
+        let mut info = match std::fs::read_to_string(&cache_path) {
+            Ok(contents) => serde_json::from_str::<BTreeMap<String, ManifestInfo>>(&contents).unwrap_or_default(),
+            Err(_) => BTreeMap::new(),
+        };
+        let missing = versions.iter().filter(|version| info.get(&version.id).is_none_or(|info| info.java_version.is_none())).map(|version| version.id.as_str()).collect::<Vec<_>>();
+        if missing.is_empty() { return Ok(info) }
+        
+        let manifest = self.api_request::<VersionManifest>(&self.config.api.mojang_version_manifest)?;
+        let mut details_failed = false;
+        for entry in manifest.versions.iter().filter(|entry| missing.contains(&entry.id.as_str())) {
+            // Release times come with the manifest, Java versions need a request per version
+            let java_version = if details_failed { None } else {
+                match self.api_request::<VersionDetails>(&entry.url) {
+                    Ok(details) => details.javaVersion.map(|java_version| java_version.majorVersion),
+                    // Keep what was fetched so far, the rest is fetched next time
+                    Err(e) => {
+                        println!("Could not get the Java version of Minecraft {}: {}", entry.id, e);
+                        details_failed = true;
+                        None
+                    }
+                }
+            };
+            info.insert(entry.id.clone(), ManifestInfo { release_time: entry.releaseTime.clone(), java_version });
+        }
+        
+        if let Some(parent) = cache_path.parent() { std::fs::create_dir_all(parent)?; }
+        write_atomic(&cache_path, serde_json::to_string_pretty(&info)? + "\n")?;
+        Ok(info)
+    }
+    
+    /// Java version a Minecraft version needs, falling back to the built-in table for versions not in the version manifest.
+    pub fn java_version(&self, mc_version: &MinecraftVersion) -> u32 {
+        self.java_versions.get(&mc_version.id).copied().unwrap_or_else(|| get_java_version(&mc_version.semver))
+    }
+}
@@ -0,0 +1,219 @@
+/// A `.properties` document as described by https://docs.oracle.com/javase/8/docs/api/java/util/Properties.html#load-java.io.Reader-
+///
+/// Edits only touch the lines of the entries they change, so comments, ordering, blank lines and line endings are kept as they were.
+#[derive(Default, Debug, Clone)]
+pub struct Properties {
+    lines: Vec<String>,
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+/// A single key/value pair, which can span multiple physical lines through line continuations.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    /// Index of the first physical line of the entry.
+    pub line: usize,
+    /// Number of physical lines the entry spans.
+    pub line_count: usize,
+    /// Length of the raw key and separator at the start of the first line.
+    prefix_len: usize,
+}
+
+impl Properties {
+    pub fn parse(source: &str) -> Self {
+        let line_ending = if source.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = source.ends_with('\n');
+        let source = source.strip_suffix('\n').unwrap_or(source);
+        let source = source.strip_suffix('\r').unwrap_or(source);
+        Self {
+            lines: if source.is_empty() && !trailing_newline { vec![] } else {
+                source.split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line).to_owned()).collect()
+            },
+            line_ending,
+            trailing_newline,
+        }
+    }
+    
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = vec![];
+        let mut index = 0;
+        while index < self.lines.len() {
+            let first = &self.lines[index];
+            let trimmed = first.trim_start_matches([' ', '\t', '\x0c']);
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                index += 1;
+                continue
+            }
+            
+            let mut line_count = 1;
+            let mut logical = String::from(trimmed);
+            while ends_with_continuation(&logical) && index + line_count < self.lines.len() {
+                logical.pop();
+                logical.push_str(self.lines[index + line_count].trim_start_matches([' ', '\t', '\x0c']));
+                line_count += 1;
+            }
+            if ends_with_continuation(&logical) { logical.pop(); }
+            
+            let (key, value_start) = split_key(&logical);
+            let prefix_len = first.len() - trimmed.len() + value_start.min(trimmed.len());
+            entries.push(Entry {
+                key: unescape(key),
+                value: unescape(&logical[value_start..]),
+                line: index,
+                line_count,
+                prefix_len,
+            });
+            index += line_count;
+        }
+        entries
+    }
+    
+    pub fn entry(&self, key: &str) -> Option<Entry> {
+        self.entries().into_iter().find(|entry| entry.key == key)
+    }
+    
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.entry(key).map(|entry| entry.value)
+    }
+    
+    /// Change the value of a property, adding it at the end of the document if it does not exist yet.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.entry(key) {
+            Some(entry) => self.replace_entry(&entry, value),
+            None => self.lines.push(format!("{}={}", escape_key(key), escape_value(value, false))),
+        }
+    }
+    
+    /// Change the value of a property, adding it after the given line if it does not exist yet.
+    pub fn set_or_insert_after(&mut self, line: usize, key: &str, value: &str) {
+        match self.entry(key) {
+            Some(entry) => self.replace_entry(&entry, value),
+            None => self.lines.insert((line + 1).min(self.lines.len()), format!("{}={}", escape_key(key), escape_value(value, false))),
+        }
+    }
+    
+    /// Add a comment line at the end of the document, separated from the previous entries by a blank line.
+    pub fn set_comment_at_end(&mut self, comment: &str) {
+        if self.lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            self.lines.push(String::new());
+        }
+        self.lines.push(comment.to_owned());
+        self.trailing_newline = true;
+    }
+    
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let entry = self.entry(key)?;
+        self.lines.drain(entry.line..entry.line + entry.line_count);
+        Some(entry.value)
+    }
+    
+    /// Index of the first comment line whose text (after the `#` or `!`) matches a predicate.
+    pub fn find_comment(&self, predicate: impl Fn(&str) -> bool) -> Option<usize> {
+        self.lines.iter().position(|line| {
+            let line = line.trim_start();
+            line.strip_prefix('#').or(line.strip_prefix('!')).is_some_and(&predicate)
+        })
+    }
+    
+    /// Replace the value of an entry. A trailing `# comment`, which .properties files treat as part of the value,
+    /// is kept as a comment line above the entry rather than overwritten.
+    fn replace_entry(&mut self, entry: &Entry, value: &str) {
+        let first = &self.lines[entry.line];
+        let old_raw = self.lines[entry.line..entry.line + entry.line_count].concat();
+        let new_line = String::from(&first[..entry.prefix_len]) + &escape_value(value, old_raw.contains("\\:") || old_raw.contains("\\="));
+        let comment = entry.value.split_once(" #").filter(|_| entry.value != value).map(|(_, comment)| {
+            let indent = &first[..first.len() - first.trim_start_matches([' ', '\t', '\x0c']).len()];
+            format!("{}#{}", indent, comment)
+        });
+        self.lines.splice(entry.line..entry.line + entry.line_count, comment.into_iter().chain([new_line]));
+    }
+}
+
+impl std::fmt::Display for Properties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 { f.write_str(self.line_ending)? }
+            f.write_str(line)?;
+        }
+        if self.trailing_newline { f.write_str(self.line_ending)? }
+        Ok(())
+    }
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Split a logical line into its raw key and the index where the value starts.
+fn split_key(line: &str) -> (&str, usize) {
+    let mut chars = line.char_indices().peekable();
+    let mut key_end = line.len();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => { chars.next(); }
+            '=' | ':' | ' ' | '\t' | '\x0c' => { key_end = i; break }
+            _ => ()
+        }
+    }
+    
+    let rest = &line[key_end..];
+    let after_whitespace = rest.trim_start_matches([' ', '\t', '\x0c']);
+    let after_separator = after_whitespace.strip_prefix(['=', ':']).map(|s| s.trim_start_matches([' ', '\t', '\x0c'])).unwrap_or(after_whitespace);
+    (&line[..key_end], line.len() - after_separator.len())
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\x0c'),
+            Some('u') => {
+                let hex = chars.by_ref().take(4).collect::<String>();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => result.push(c),
+                    None => { result.push_str("\\u"); result.push_str(&hex); }
+                }
+            }
+            Some(c) => result.push(c),
+            None => (),
+        }
+    }
+    result
+}
+
+fn escape(s: &str, escape_leading_space: bool, escape_chars: &[char]) -> String {
+    let mut result = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\x0c' => result.push_str("\\f"),
+            ' ' if i == 0 && escape_leading_space => result.push_str("\\ "),
+            c if escape_chars.contains(&c) => { result.push('\\'); result.push(c); }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_key(key: &str) -> String {
+    let mut result = escape(key, true, &['=', ':', ' ']);
+    if result.starts_with(['#', '!']) { result.insert(0, '\\'); }
+    result
+}
+
+fn escape_value(value: &str, escape_separators: bool) -> String {
+    escape(value, true, if escape_separators { &['=', ':'] } else { &[] })
+}
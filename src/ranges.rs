@@ -0,0 +1,104 @@
+use crate::{app::App, common::*, semantic_version::VersionRangeSet};
+
+
+/// Split a range expression into words, keeping quoted predicates and bracketed lists together.
+pub fn tokenize_range_expression(s: &str) -> Result<Vec<String>> {
+    let mut tokens = vec![];
+    let mut chars = s.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue
+        }
+        let closing = match c {
+            '"' => Some('"'),
+            '[' => Some(']'),
+            _ => None,
+        };
+        let mut token = String::new();
+        match closing {
+            Some(closing) => {
+                token.push(c);
+                chars.next();
+                loop {
+                    let c = chars.next().ok_or(format!("Missing '{}' in range expression.", closing))?;
+                    token.push(c);
+                    if c == closing { break }
+                }
+            }
+            None => while let Some(&c) = chars.peek() && !c.is_whitespace() {
+                token.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+impl App {
+    /// Value of a single operand of a range expression.
+    fn range_operand(&self, token: &str) -> Result<VersionRangeSet> {
+        match token {
+            "confirmed" => self.get_current_ranges(),
+            "known" => Ok(VersionRangeSet::from_versions(self.mc_versions.iter().map(|(version, _)| &version.semver))),
+            _ => match token.strip_prefix("dep:") {
+                Some(name) => self.dependency_game_versions(name),
+                None => token.parse(),
+            }
+        }
+    }
+    
+    /// Evaluate a range expression: operands joined left to right by `and`, `or` and `minus`, each optionally preceded by `not`, which means the known versions not in the operand.
+    /// Operands are `confirmed`, `known`, `dep:<name>` or a version range like `">=1.19 <1.21"`.
+    pub fn evaluate_range_expression(&self, s: &str) -> Result<VersionRangeSet> {
+        let tokens = tokenize_range_expression(s)?;
+        let mut tokens = tokens.iter().map(String::as_str);
+        
+        // Negation is relative to the known versions, so `not confirmed` doesn't run off to infinity
+        let known = self.mc_versions.iter().map(|(version, _)| version.semver.clone()).collect::<Vec<_>>();
+        let operand = |tokens: &mut dyn Iterator<Item = &str>| -> Result<VersionRangeSet> {
+            let mut negate = false;
+            loop {
+                match tokens.next() {
+                    Some("not") => negate = !negate,
+                    Some(token) => {
+                        let set = self.range_operand(token)?;
+                        return Ok(if negate { set.complement_within(&known) } else { set })
+                    }
+                    None => return Err("Range expression ends without an operand.".into()),
+                }
+            }
+        };
+        
+        let mut result = operand(&mut tokens)?;
+        while let Some(operator) = tokens.next() {
+            let other = operand(&mut tokens)?;
+            result = match operator {
+                "and" => result.intersection(&other),
+                "or" => result.union(&other),
+                "minus" => result.difference(&other),
+                _ => return Err(format!("Unknown range operator '{}', expected and, or or minus.", operator).into()),
+            };
+        }
+        Ok(result)
+    }
+    
+    /// Print the result of a range expression and the known Minecraft versions in it.
+    pub fn print_range(&self, s: &str) -> Result<()> {
+        let set = self.evaluate_range_expression(s)?;
+        println!("Range: {}", set);
+        let versions = self.mc_versions.iter().rev().map(|(version, _)| version).filter(|version| set.contains(&version.semver)).collect::<Vec<_>>();
+        if versions.is_empty() {
+            println!("No known Minecraft versions.");
+        } else {
+            print!("Known Minecraft versions: ");
+            for (i, version) in versions.iter().enumerate() {
+                if i > 0 { print!(", "); }
+                print!("{}", version);
+            }
+            println!();
+        }
+        Ok(())
+    }
+}
@@ -2,35 +2,81 @@ use std::cmp::Ordering;
 
 use crate::common::*;
 
-/// Version information and formatting as defined by https://semver.org/
+/// Version information and formatting as defined by https://semver.org/, with any number of numeric components like Fabric Loader allows.
+/// Build metadata is kept, but ignored when comparing versions, so versions differing only in it are equal.
 #[derive(Default, Debug, Clone)]
 pub struct SemanticVersion {
     pub major: u32,
     pub minor: u32,
     pub patch: u32,
+    /// Numeric components after the patch, like the 4 of `1.2.3.4`.
+    pub extra: Vec<u32>,
     pub release: Option<String>,
     pub build: Option<String>,
 }
 
 impl SemanticVersion {
-    pub fn next_version(mut self) -> Self {
-        self.release = match self.release {
-            None => Some("".to_owned()),
-            Some(s) => Some(s + "."),
-        };
-        self
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch, extra: Vec::new(), release: None, build: None }
+    }
+    
+    /// Version made of numeric components, major first. Missing components are zero.
+    pub fn from_components(components: &[u32]) -> Self {
+        let component = |i: usize| components.get(i).copied().unwrap_or(0);
+        Self { extra: components.get(3..).unwrap_or_default().to_vec(), ..Self::new(component(0), component(1), component(2)) }
+    }
+    
+    /// Every numeric component, major first.
+    pub fn components(&self) -> impl Iterator<Item = u32> + '_ {
+        [self.major, self.minor, self.patch].into_iter().chain(self.extra.iter().copied())
     }
     
     pub fn matches_numbers(&self, other: &Self) -> bool {
-        self.major == other.major && self.minor == other.minor && self.patch == other.patch
+        compare_components(self, other) == Ordering::Equal
+    }
+}
+
+fn compare_components(a: &SemanticVersion, b: &SemanticVersion) -> Ordering {
+    let length = usize::max(a.extra.len(), b.extra.len()) + 3;
+    let (a, b) = (a.components().chain(std::iter::repeat(0)).take(length), b.components().chain(std::iter::repeat(0)).take(length));
+    a.cmp(b)
+}
+
+/// Compare prerelease strings by their dot separated identifiers: numbers numerically and before other identifiers, which compare as text.
+/// The empty prerelease has no identifiers, so it comes before every other prerelease.
+fn compare_prereleases(a: &str, b: &str) -> Ordering {
+    fn identifiers(s: &str) -> Vec<&str> {
+        if s.is_empty() { vec![] } else { s.split('.').collect() }
+    }
+    let (a, b) = (identifiers(a), identifiers(b));
+    for (a, b) in a.iter().zip(&b) {
+        let is_number = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        let ordering = match (is_number(a), is_number(b)) {
+            // Compare by length first so numbers of any size work
+            (true, true) => a.trim_start_matches('0').len().cmp(&b.trim_start_matches('0').len()).then_with(|| a.trim_start_matches('0').cmp(b.trim_start_matches('0'))),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal { return ordering }
     }
+    a.len().cmp(&b.len())
+}
+
+/// Whether a prerelease or build string is made of dot separated identifiers of ASCII letters, digits and hyphens.
+pub(crate) fn is_identifiers(s: &str) -> bool {
+    s.split('.').all(|identifier| !identifier.is_empty() && identifier.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
 }
 
 impl std::fmt::Display for SemanticVersion {
+    /// Trailing zero components are left out, so equal versions are written the same.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.major)?;
-        if self.minor > 0 || self.patch > 0 { write!(f, ".{}", self.minor)? }
-        if self.patch > 0 { write!(f, ".{}", self.patch)? }
+        let components = self.components().collect::<Vec<_>>();
+        let length = components.iter().rposition(|&component| component > 0).map_or(1, |i| i + 1);
+        for (i, component) in components[..length].iter().enumerate() {
+            if i > 0 { write!(f, ".")? }
+            write!(f, "{}", component)?;
+        }
         if let Some(s) = &self.release { write!(f, "-{s}")? }
         if let Some(s) = &self.build   { write!(f, "+{s}")? }
         Ok(())
@@ -40,34 +86,29 @@ impl std::fmt::Display for SemanticVersion {
 impl std::str::FromStr for SemanticVersion {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self> {
-        let (s, build) = match s.split_once("+") {
-            Some((s, build)) => (s, Some(build.to_owned())),
+        let (core, build) = match s.split_once("+") {
+            Some((core, build)) if is_identifiers(build) => (core, Some(build.to_owned())),
+            Some((_, build)) => return Err(format!("Invalid build metadata '{}' in version '{}'.", build, s).into()),
             None => (s, None),
         };
-        let (s, release) = match s.split_once("-") {
-            Some((s, release)) => (s, Some(release.to_owned())),
-            None => (s, None),
+        // The empty prerelease is allowed, ranges use it for the lowest version with some numbers
+        let (core, release) = match core.split_once("-") {
+            Some((core, release)) if release.is_empty() || is_identifiers(release) => (core, Some(release.to_owned())),
+            Some((_, release)) => return Err(format!("Invalid prerelease '{}' in version '{}'.", release, s).into()),
+            None => (core, None),
         };
-        let mut parts = s.splitn(3, ".");
-        Ok(Self {
-            major: parts.next().ok_or("No major version")?.parse()?,
-            minor: match parts.next() {
-                Some(s) => s.parse()?,
-                None => 0,
-            },
-            patch: match parts.next() {
-                Some(s) => s.parse()?,
-                None => 0,
-            },
-            release,
-            build,
-        })
+        let components = core.split('.').map(|component| match component {
+            "" => Err(format!("Empty version component in '{}'.", s).into()),
+            _ if !component.bytes().all(|b| b.is_ascii_digit()) => Err(format!("Invalid version component '{}' in '{}'.", component, s).into()),
+            _ => component.parse::<u32>().map_err(Error::from),
+        }).collect::<Result<Vec<_>>>()?;
+        Ok(Self { release, build, ..Self::from_components(&components) })
     }
 }
 
 impl PartialEq for SemanticVersion {
     fn eq(&self, other: &Self) -> bool {
-        self.major == other.major && self.minor == other.minor && self.patch == other.patch && self.release == other.release
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -75,42 +116,12 @@ impl Eq for SemanticVersion {}
 
 impl Ord for SemanticVersion {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.major.cmp(&other.major) {
-            Ordering::Equal => match self.minor.cmp(&other.minor) {
-                Ordering::Equal => match self.patch.cmp(&other.patch) {
-                    Ordering::Equal => match (&self.release, &other.release) {
-                        (None, None) => Ordering::Equal,
-                        (None, Some(_)) => Ordering::Greater,
-                        (Some(_), None) => Ordering::Less,
-                        (Some(self_release), Some(other_release)) => {
-                            let mut self_parts = self_release.split(".");
-                            let mut other_parts = other_release.split(".");
-                            loop {
-                                match (self_parts.next(), other_parts.next()) {
-                                    (None, None) => break Ordering::Equal,
-                                    (None, Some(_)) => break Ordering::Less,
-                                    (Some(_), None) => break Ordering::Greater,
-                                    (Some(self_part), Some(other_part)) => {
-                                        let self_number = if self_part == "" { Ok(0u32) } else { self_part.parse() };
-                                        let other_number = if other_part == "" { Ok(0u32) } else { other_part.parse() };
-                                        let cmp = match (self_number, other_number) {
-                                            (Err(_), Err(_)) => self_part.cmp(other_part),
-                                            (Err(_), Ok(_)) => break Ordering::Greater,
-                                            (Ok(_), Err(_)) => break Ordering::Less,
-                                            (Ok(a), Ok(b)) => a.cmp(&b),
-                                        };
-                                        if cmp != Ordering::Equal { break cmp }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    result => result
-                }
-                result => result
-            }
-            result => result
-        }
+        compare_components(self, other).then_with(|| match (&self.release, &other.release) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(self_release), Some(other_release)) => compare_prereleases(self_release, other_release),
+        })
     }
 }
 
@@ -121,7 +132,7 @@ impl PartialOrd for SemanticVersion {
 }
 
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum VersionMatchType {
     EqualTo,
     GreaterThan,
@@ -134,138 +145,432 @@ pub enum VersionMatchType {
 
 
 /// Version range as specified by https://wiki.fabricmc.net/documentation:fabric_mod_json_spec#versionrange
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct SemanticVersionRange {
     pub start: Option<SemanticVersion>,
     pub end: Option<SemanticVersion>,
+    /// Whether `start` itself is left out of the range.
+    pub start_exclusive: bool,
+    /// Whether `end` itself is part of the range.
+    pub end_inclusive: bool,
 }
 
 impl SemanticVersionRange {
     pub fn contains(&self, version: &SemanticVersion) -> bool {
-        self.start.as_ref().map(|start| *version >= *start).unwrap_or(true) && self.end.as_ref().map(|end| *version < *end).unwrap_or(true)
+        let after_start = match &self.start {
+            Some(start) if self.start_exclusive => version > start,
+            Some(start) => version >= start,
+            None => true,
+        };
+        let before_end = match &self.end {
+            Some(end) if self.end_inclusive => version <= end,
+            Some(end) => version < end,
+            None => true,
+        };
+        after_start && before_end
+    }
+    
+    /// This range as an X-range like `1.x` or `1.20.x`, if it is exactly one, prereleases included.
+    fn x_range(&self) -> Option<String> {
+        let (Some(start), Some(end)) = (&self.start, &self.end) else { return None };
+        if self.start_exclusive || self.end_inclusive { return None }
+        if *start == lowest_with(&[start.major]) && *end == lowest_with(&[start.major + 1]) {
+            Some(format!("{}.x", start.major))
+        } else if *start == lowest_with(&[start.major, start.minor]) && *end == lowest_with(&[start.major, start.minor + 1]) {
+            Some(format!("{}.{}.x", start.major, start.minor))
+        } else { None }
+    }
+    
+    fn lower_cut(&self) -> Cut {
+        match &self.start {
+            Some(start) if self.start_exclusive => Cut::Above(start.clone()),
+            Some(start) => Cut::Below(start.clone()),
+            None => Cut::NegativeInfinity,
+        }
+    }
+    
+    fn upper_cut(&self) -> Cut {
+        match &self.end {
+            Some(end) if self.end_inclusive => Cut::Above(end.clone()),
+            Some(end) => Cut::Below(end.clone()),
+            None => Cut::PositiveInfinity,
+        }
+    }
+    
+    fn from_cuts(lower: Cut, upper: Cut) -> Self {
+        let (start, start_exclusive) = match lower {
+            Cut::Below(start) => (Some(start), false),
+            Cut::Above(start) => (Some(start), true),
+            _ => (None, false),
+        };
+        let (end, end_inclusive) = match upper {
+            Cut::Below(end) => (Some(end), false),
+            Cut::Above(end) => (Some(end), true),
+            _ => (None, false),
+        };
+        Self { start, end, start_exclusive, end_inclusive }
     }
 }
 
 impl std::fmt::Display for SemanticVersionRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let start_operator = if self.start_exclusive { ">" } else { ">=" };
+        let end_operator = if self.end_inclusive { "<=" } else { "<" };
+        if let Some(x_range) = self.x_range() { return write!(f, "{}", x_range) }
         match (&self.start, &self.end) {
+            (Some(start), Some(end)) if start == end && !self.start_exclusive && self.end_inclusive => write!(f, "{}", start),
             (None, None) => write!(f, "*"),
-            (Some(start), None) => write!(f, ">={}", start),
-            (None, Some(end)) => write!(f, "<{}", end),
-            (Some(start), Some(end)) => write!(f, ">={} <{}", start, end),
+            (Some(start), None) => write!(f, "{}{}", start_operator, start),
+            (None, Some(end)) => write!(f, "{}{}", end_operator, end),
+            (Some(start), Some(end)) => write!(f, "{}{} {}{}", start_operator, start, end_operator, end),
         }
     }
 }
 
+/// Point between versions that a range starts or ends at. Every version lies between its own `Below` and `Above` cuts,
+/// so ranges are the versions between two cuts, whatever their inclusive and exclusive bounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cut {
+    NegativeInfinity,
+    Below(SemanticVersion),
+    Above(SemanticVersion),
+    PositiveInfinity,
+}
+
+impl Cut {
+    fn key(&self) -> (u8, Option<&SemanticVersion>, u8) {
+        match self {
+            Cut::NegativeInfinity => (0, None, 0),
+            Cut::Below(version) => (1, Some(version), 0),
+            Cut::Above(version) => (1, Some(version), 1),
+            Cut::PositiveInfinity => (2, None, 0),
+        }
+    }
+}
+
+impl Ord for Cut {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for Cut {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl std::str::FromStr for SemanticVersionRange {
     type Err = Error;
+    /// Parse predicates separated by whitespace, which all have to match, like `>=1.20 <1.21`.
     fn from_str(s: &str) -> Result<Self> {
-        let mut range = SemanticVersionRange::default();
-        for mut s in s.split_whitespace() {
-            let (start, end) = {
-                let mut match_type = VersionMatchType::EqualTo;
-                for (operator, mt) in [
-                    (">=", VersionMatchType::GreaterThanOrEqualTo),
-                    ("<=", VersionMatchType::LessThanOrEqualTo),
-                    (">" , VersionMatchType::GreaterThan),
-                    ("<" , VersionMatchType::LessThan),
-                    ("=" , VersionMatchType::EqualTo),
-                    ("^" , VersionMatchType::MatchMajorVersion),
-                    ("~" , VersionMatchType::MatchMinorVersion),
-                ] {
-                    if let Some(remaining) = s.strip_prefix(operator) {
-                        s = remaining;
-                        match_type = mt;
-                        break
-                    }
-                }
-                
-                let (s, build) = match s.split_once("+") {
-                    Some((s, build)) => (s, Some(build.to_owned())),
-                    None => (s, None),
-                };
-                let (s, release) = match s.split_once("-") {
-                    Some((s, release)) => (s, Some(release.to_owned())),
-                    None => (s, None),
-                };
-                let mut parts = s.splitn(3, ".");
-                
-                let major = parts.next().ok_or("No major version")?.parse()?;
-                let minor = match parts.next() {
-                    Some("X") | Some("x") | Some("*") => {
-                        match_type = VersionMatchType::MatchMajorVersion;
-                        0
-                    }
-                    Some(s) => s.parse()?,
-                    None => 0,
-                };
-                let patch = match parts.next() {
-                    Some("X") | Some("x") | Some("*") => {
-                        if match_type != VersionMatchType::MatchMajorVersion {
-                            match_type = VersionMatchType::MatchMinorVersion;
-                        }
-                        0
-                    }
-                    Some(s) => s.parse()?,
-                    None => 0,
-                };
-                
-                let base_version = SemanticVersion { major, minor, patch, release, build };
-                
-                match match_type {
-                    VersionMatchType::EqualTo => (Some(base_version.clone()), Some(base_version)),
-                    VersionMatchType::GreaterThanOrEqualTo => (Some(base_version), None),
-                    VersionMatchType::LessThan => (None, Some(base_version)),
-                    VersionMatchType::GreaterThan => (Some(base_version.next_version()), None),
-                    VersionMatchType::LessThanOrEqualTo => (None, Some(base_version.next_version())),
-                    VersionMatchType::MatchMajorVersion => (
-                        Some(SemanticVersion { major: base_version.major    , minor: 0, patch: 0, release: Some("".to_owned()), build: None }),
-                        Some(SemanticVersion { major: base_version.major + 1, minor: 0, patch: 0, release: Some("".to_owned()), build: None }),
-                    ),
-                    VersionMatchType::MatchMinorVersion => (
-                        Some(SemanticVersion { major: base_version.major, minor: base_version.minor    , patch: 0, release: Some("".to_owned()), build: None }),
-                        Some(SemanticVersion { major: base_version.major, minor: base_version.minor + 1, patch: 0, release: Some("".to_owned()), build: None }),
-                    ),
-                }
-            };
-            
-            match (start, &range.start) {
-                (Some(new_start), Some(current_start)) => if new_start > *current_start { range.start = Some(new_start) }
-                (Some(new_start), None) => range.start = Some(new_start),
-                (None, _) => ()
+        let (mut lower, mut upper) = (Cut::NegativeInfinity, Cut::PositiveInfinity);
+        for predicate in s.split_whitespace() {
+            let (start, end) = predicate_cuts(predicate)?;
+            // Every predicate narrows the range
+            lower = lower.max(start);
+            upper = upper.min(end);
+        }
+        Ok(SemanticVersionRange::from_cuts(lower, upper))
+    }
+}
+
+/// Lowest version with some numeric components, below all of their prereleases.
+fn lowest_with(components: &[u32]) -> SemanticVersion {
+    SemanticVersion { release: Some("".to_owned()), ..SemanticVersion::from_components(components) }
+}
+
+/// Cuts around the versions a single predicate matches, following https://wiki.fabricmc.net/documentation:fabric_mod_json_spec#versionrange
+/// and npm's caret ranges for versions starting with 0, where `^0.2.3` stays below 0.3.
+fn predicate_cuts(predicate: &str) -> Result<(Cut, Cut)> {
+    if predicate == "*" { return Ok((Cut::NegativeInfinity, Cut::PositiveInfinity)) }
+    
+    let mut s = predicate;
+    let mut match_type = None;
+    for (operator, mt) in [
+        (">=", VersionMatchType::GreaterThanOrEqualTo),
+        ("<=", VersionMatchType::LessThanOrEqualTo),
+        (">" , VersionMatchType::GreaterThan),
+        ("<" , VersionMatchType::LessThan),
+        ("=" , VersionMatchType::EqualTo),
+        ("^" , VersionMatchType::MatchMajorVersion),
+        ("~" , VersionMatchType::MatchMinorVersion),
+    ] {
+        if let Some(remaining) = s.strip_prefix(operator) {
+            s = remaining;
+            match_type = Some(mt);
+            break
+        }
+    }
+    
+    // X-ranges, like 1.x for 1 and its minor versions or 1.2.x for 1.2 and its patches, in any position after the major
+    let core = s.split(['-', '+']).next().unwrap_or_default();
+    let parts = core.split('.').collect::<Vec<_>>();
+    if let Some(position) = parts.iter().position(|part| matches!(*part, "x" | "X" | "*")) {
+        if match_type.is_some_and(|mt| mt != VersionMatchType::EqualTo) {
+            return Err(format!("Wildcard version '{}' can't have an operator other than '='.", predicate).into())
+        }
+        if position == 0 {
+            return Err(format!("The major version can't be a wildcard, unlike in '{}'.", predicate).into())
+        }
+        // Like 1.x.x, which is the same as 1.x
+        if !parts[position..].iter().all(|part| matches!(*part, "x" | "X" | "*")) {
+            return Err(format!("Wildcards can only be followed by more wildcards, unlike in '{}'.", predicate).into())
+        }
+        if s.len() > core.len() && s.as_bytes()[core.len()] == b'-' {
+            return Err(format!("Wildcard version '{}' can't have a prerelease.", predicate).into())
+        }
+        let mut components = parts[..position].iter().map(|part| part.parse::<u32>()).collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| format!("Invalid version '{}'.", predicate))?;
+        let start = lowest_with(&components);
+        *components.last_mut().unwrap_or(&mut 0) += 1;
+        return Ok((Cut::Below(start), Cut::Below(lowest_with(&components))))
+    }
+    
+    let version = s.parse::<SemanticVersion>()?;
+    Ok(match match_type.unwrap_or(VersionMatchType::EqualTo) {
+        VersionMatchType::EqualTo => (Cut::Below(version.clone()), Cut::Above(version)),
+        VersionMatchType::GreaterThanOrEqualTo => (Cut::Below(version), Cut::PositiveInfinity),
+        VersionMatchType::LessThan => (Cut::NegativeInfinity, Cut::Below(version)),
+        VersionMatchType::GreaterThan => (Cut::Above(version), Cut::PositiveInfinity),
+        VersionMatchType::LessThanOrEqualTo => (Cut::NegativeInfinity, Cut::Above(version)),
+        VersionMatchType::MatchMajorVersion => {
+            // The first non-zero component is the one that has to stay the same, or the last written one if they're all zero
+            let written = parts.len().min(3);
+            let components = version.components().take(written).collect::<Vec<_>>();
+            let kept = components.iter().position(|&component| component > 0).unwrap_or(written - 1);
+            let mut end = components[..=kept].to_vec();
+            end[kept] += 1;
+            (Cut::Below(version), Cut::Below(lowest_with(&end)))
+        }
+        VersionMatchType::MatchMinorVersion => {
+            let end = [version.major, version.minor + 1];
+            (Cut::Below(version), Cut::Below(lowest_with(&end)))
+        }
+    })
+}
+
+/// Union of version ranges, like the value of a fabric.mod.json dependency.
+/// Parses a JSON array of predicates, predicates joined by `||` and hyphen ranges like `1.20 - 1.20.4`,
+/// and is written as a JSON array of predicates.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct VersionRangeSet {
+    pub ranges: Vec<SemanticVersionRange>,
+}
+
+impl VersionRangeSet {
+    pub fn contains(&self, version: &SemanticVersion) -> bool {
+        self.ranges.iter().any(|range| range.contains(version))
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    
+    pub fn insert(&mut self, range: SemanticVersionRange) {
+        self.ranges.push(range);
+    }
+    
+    /// Sort and merge the ranges.
+    pub fn simplified(self) -> Self {
+        Self { ranges: simplify_range_set(self.ranges) }
+    }
+    
+    /// The ranges as sorted, disjoint and non-empty pairs of cuts.
+    fn intervals(&self) -> Vec<(Cut, Cut)> {
+        let mut intervals = self.ranges.iter().map(|range| (range.lower_cut(), range.upper_cut())).filter(|(lower, upper)| lower < upper).collect::<Vec<_>>();
+        intervals.sort();
+        let mut merged: Vec<(Cut, Cut)> = vec![];
+        for (lower, upper) in intervals {
+            match merged.last_mut() {
+                Some((_, last_upper)) if lower <= *last_upper => if upper > *last_upper { *last_upper = upper },
+                _ => merged.push((lower, upper)),
             }
-            match (end, &range.end) {
-                (Some(new_end), Some(current_end)) => if new_end < *current_end { range.end = Some(new_end) }
-                (Some(new_end), None) => range.end = Some(new_end),
-                (None, _) => ()
+        }
+        merged
+    }
+    
+    fn from_intervals(intervals: Vec<(Cut, Cut)>) -> Self {
+        Self { ranges: intervals.into_iter().map(|(lower, upper)| SemanticVersionRange::from_cuts(lower, upper)).collect() }
+    }
+    
+    /// Versions in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_intervals(Self { ranges: self.ranges.iter().chain(&other.ranges).cloned().collect() }.intervals())
+    }
+    
+    /// Versions in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        for (lower, upper) in self.intervals() {
+            for (other_lower, other_upper) in other.intervals() {
+                let lower = lower.clone().max(other_lower);
+                let upper = upper.clone().min(other_upper);
+                if lower < upper { intervals.push((lower, upper)); }
             }
         }
+        Self::from_intervals(Self::from_intervals(intervals).intervals())
+    }
+    
+    /// Versions in this set but not the other.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+    
+    /// Every version not in this set.
+    pub fn complement(&self) -> Self {
+        let mut intervals = vec![];
+        let mut lower = Cut::NegativeInfinity;
+        for (next_lower, next_upper) in self.intervals() {
+            if lower < next_lower { intervals.push((lower, next_lower)); }
+            lower = next_upper;
+        }
+        if lower < Cut::PositiveInfinity { intervals.push((lower, Cut::PositiveInfinity)); }
+        Self::from_intervals(intervals)
+    }
+    
+    /// The known versions not in this set, written as compactly as `compacted` does.
+    /// Unlike `complement`, this never claims versions past the known ones.
+    pub fn complement_within(&self, known: &[SemanticVersion]) -> Self {
+        Self::from_versions(known.iter().filter(|version| !self.contains(version))).compacted(known)
+    }
+    
+    /// Set of exactly the given versions.
+    pub fn from_versions<'a>(versions: impl IntoIterator<Item = &'a SemanticVersion>) -> Self {
+        Self::from_intervals(Self { ranges: versions.into_iter().map(|version| SemanticVersionRange {
+            start: Some(version.clone()),
+            end: Some(version.clone()),
+            start_exclusive: false,
+            end_inclusive: true,
+        }).collect() }.intervals())
+    }
+    
+    /// Shortest ranges holding the same known versions as this set, ending at the newest known version in it
+    /// rather than at the next one, so that no newer version is claimed before it is confirmed.
+    /// A whole minor line is written as `1.20.x`, once a newer line is known and it can't grow any more.
+    pub fn compacted(&self, known: &[SemanticVersion]) -> Self {
+        let mut known = known.to_vec();
+        known.sort();
+        known.dedup();
+        let same_line = |a: &SemanticVersion, b: &SemanticVersion| a.major == b.major && a.minor == b.minor;
         
-        Ok(range)
+        let mut ranges = vec![];
+        let mut i = 0;
+        while i < known.len() {
+            if !self.contains(&known[i]) {
+                i += 1;
+                continue
+            }
+            let mut j = i;
+            while j + 1 < known.len() && self.contains(&known[j + 1]) { j += 1 }
+            
+            let (first, last) = (&known[i], &known[j]);
+            let whole_line = same_line(first, last) && (i == 0 || !same_line(&known[i - 1], first)) && j + 1 < known.len() && !same_line(&known[j + 1], last);
+            ranges.push(if i == j {
+                SemanticVersionRange { start: Some(first.clone()), end: Some(first.clone()), start_exclusive: false, end_inclusive: true }
+            } else if whole_line && i < j {
+                let line_start = |minor| lowest_with(&[first.major, minor]);
+                SemanticVersionRange { start: Some(line_start(first.minor)), end: Some(line_start(first.minor + 1)), ..Default::default() }
+            } else {
+                SemanticVersionRange { start: Some(first.clone()), end: Some(last.clone()), start_exclusive: false, end_inclusive: true }
+            });
+            i = j + 1;
+        }
+        Self { ranges }
     }
-}
-
-/// Sort and merge overlapping ranges.
-pub fn simplify_range_set(mut ranges: Vec<SemanticVersionRange>) -> Vec<SemanticVersionRange> {
-    ranges.sort_by(|a, b| a.start.cmp(&b.start));
-    let mut sorted_ranges = ranges.into_iter();
-    let mut merged_ranges = vec![];
     
-    let mut current = match sorted_ranges.next() {
-        Some(range) => range,
-        None => return merged_ranges,
-    };
+    /// The versions of a list that are in this set.
+    pub fn filter<'a>(&self, versions: impl IntoIterator<Item = &'a SemanticVersion>) -> Vec<&'a SemanticVersion> {
+        versions.into_iter().filter(|version| self.contains(version)).collect()
+    }
     
-    for next in sorted_ranges {
-        if next.start <= current.end {
-            current.end = next.end;
-        } else {
-            merged_ranges.push(current);
-            current = next;
+    /// Union of version predicates. A blank predicate means no versions rather than every version.
+    fn from_predicates(predicates: &[String]) -> Result<Self> {
+        let mut set = VersionRangeSet::default();
+        for predicate in predicates.iter().filter(|predicate| !predicate.trim().is_empty()) {
+            set.parse_predicate(predicate).map_err(|e| format!("Invalid version range '{}': {}", predicate, e))?;
         }
+        Ok(set)
     }
     
-    merged_ranges.push(current);
-    merged_ranges
+    /// Parse one predicate string, which may join alternatives with `||`.
+    fn parse_predicate(&mut self, s: &str) -> Result<()> {
+        for alternative in s.split("||") {
+            if alternative.trim().is_empty() { return Err("Empty alternative between '||'.".into()) }
+            // Hyphen ranges are inclusive on both ends
+            let words = alternative.split_whitespace().collect::<Vec<_>>();
+            let mut predicates = vec![];
+            let mut i = 0;
+            while i < words.len() {
+                if words.get(i + 1) == Some(&"-") && let Some(end) = words.get(i + 2) {
+                    predicates.push(format!(">={} <={}", words[i], end));
+                    i += 3;
+                } else {
+                    predicates.push(words[i].to_owned());
+                    i += 1;
+                }
+            }
+            self.ranges.push(predicates.join(" ").parse()?);
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for VersionRangeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 { write!(f, ", ")? }
+            write!(f, "\"{}\"", range)?;
+        }
+        write!(f, "]")
+    }
+}
+
+serde_as_string!(SemanticVersion, SemanticVersionRange);
+
+/// Stored as a list of predicates, like in fabric.mod.json. A single predicate string is read too.
+impl serde::Serialize for VersionRangeSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.ranges.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VersionRangeSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Predicates {
+            List(Vec<String>),
+            Single(String),
+        }
+        let predicates = match Predicates::deserialize(deserializer)? {
+            Predicates::List(predicates) => predicates,
+            Predicates::Single(predicate) => vec![predicate],
+        };
+        VersionRangeSet::from_predicates(&predicates).map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for VersionRangeSet {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let predicates = if s.starts_with('[') {
+            serde_json::from_str::<Vec<String>>(s).map_err(|e| format!("Invalid version range list '{}': {}", s, e))?
+        } else if s.starts_with('"') {
+            vec![serde_json::from_str::<String>(s).map_err(|e| format!("Invalid version range '{}': {}", s, e))?]
+        } else {
+            vec![s.to_owned()]
+        };
+        
+        VersionRangeSet::from_predicates(&predicates)
+    }
+}
+
+/// Sort and merge overlapping and touching ranges, dropping empty ones.
+/// Missing bounds count as infinitely far out, so an unbounded range swallows everything past its start.
+pub fn simplify_range_set(ranges: Vec<SemanticVersionRange>) -> Vec<SemanticVersionRange> {
+    VersionRangeSet::from_intervals(VersionRangeSet { ranges }.intervals()).ranges
 }
 
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{app::App, common::*, config::MappingMode, minecraft_version::MinecraftVersion, properties::Properties, semantic_version::VersionRangeSet};
+
+
+/// Where a project stands, read from its gradle properties into typed values.
+/// Saved as JSON after every recorded command, for tools that would rather not parse gradle.properties and for `status` to point out edits made by hand.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectState {
+    pub minecraft_version: MinecraftVersion,
+    /// Yarn build the mappings use, `None` with Mojang's mappings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yarn_build: Option<u32>,
+    pub java_version: u32,
+    /// Minecraft versions the mod is confirmed to work with.
+    pub compatible_ranges: VersionRangeSet,
+    pub enforce_range: bool,
+    pub dependencies: Vec<DependencyState>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DependencyState {
+    pub name: String,
+    pub version: String,
+    /// Versions of the dependency the mod declares it works with, from its `_version_range` property.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<VersionRangeSet>,
+    /// Whether it is an optional dependency left out because it doesn't support `minecraft_version`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+/// What differs between a saved state and the current one, one line per difference.
+pub fn state_changes(saved: &ProjectState, current: &ProjectState) -> Vec<String> {
+    let mut changes = vec![];
+    let mut compare = |name: &str, saved: String, current: String| {
+        if saved != current { changes.push(format!("{} {} -> {}", name, saved, current)); }
+    };
+    let build = |build: Option<u32>| build.map_or("none".to_owned(), |build| build.to_string());
+    compare("Minecraft", saved.minecraft_version.to_string(), current.minecraft_version.to_string());
+    compare("yarn build", build(saved.yarn_build), build(current.yarn_build));
+    compare("Java", saved.java_version.to_string(), current.java_version.to_string());
+    compare("compatible ranges", saved.compatible_ranges.to_string(), current.compatible_ranges.to_string());
+    compare("enforce range", saved.enforce_range.to_string(), current.enforce_range.to_string());
+    
+    let version = |state: &ProjectState, name: &str| state.dependencies.iter().find(|d| d.name == name).map_or("none".to_owned(), |d| d.version.clone());
+    let mut names = saved.dependencies.iter().chain(&current.dependencies).map(|d| d.name.as_str()).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    for name in names {
+        compare(name, version(saved, name), version(current, name));
+    }
+    changes
+}
+
+impl App {
+    /// Read the state of the project from its gradle properties.
+    pub fn project_state(&self, properties: &Properties) -> Result<ProjectState> {
+        let names = &self.config.properties;
+        let yarn_build = match self.config.mappings {
+            MappingMode::Yarn => {
+                let mappings = self.find_property(properties, &names.yarn_mappings)?;
+                let build = mappings.rsplit_once("+build.").ok_or(format!("Invalid yarn mappings '{}'.", mappings))?.1;
+                Some(build.trim().parse()?)
+            }
+            _ => None,
+        };
+        
+        let disabled = self.find_list_property(properties, &names.disabled_dependencies);
+        let mut dependencies = vec![];
+        if let Some(marker) = self.find_ralli_marker(properties) {
+            for entry in properties.entries() {
+                if entry.line < marker || names.is_ralli_property(&entry.key) { continue }
+                let range = properties.get(&names.dependency_range(&entry.key)).map(|range| range.parse()).transpose()?;
+                dependencies.push(DependencyState { disabled: disabled.contains(&entry.key), name: entry.key, version: entry.value, range });
+            }
+        }
+        
+        Ok(ProjectState {
+            minecraft_version: self.find_property(properties, &names.minecraft_version)?.parse()?,
+            yarn_build,
+            java_version: self.find_property(properties, &names.java_version)?.trim().parse()?,
+            compatible_ranges: self.parse_current_ranges(properties)?,
+            enforce_range: self.find_property(properties, &names.enforce_range)?.trim() == "true",
+            dependencies,
+        })
+    }
+    
+    /// The last saved state of the project, if it has one.
+    pub fn load_project_state(&self) -> Result<Option<ProjectState>> {
+        let path = self.cwd.join(&self.config.paths.state);
+        if !std::fs::exists(&path)? { return Ok(None) }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(&path)?).map_err(|e| format!("Invalid {}: {}", self.config.paths.state, e))?))
+    }
+    
+    pub fn save_project_state(&self, state: &ProjectState) -> Result<()> {
+        let path = self.cwd.join(&self.config.paths.state);
+        if let Some(parent) = path.parent() { std::fs::create_dir_all(parent)?; }
+        write_atomic(&path, serde_json::to_string_pretty(state)? + "\n")
+    }
+}
@@ -1,4 +1,4 @@
-use crate::semantic_version::{SemanticVersion, simplify_range_set};
+use crate::{api_structs::{VersionDetails, VersionManifest}, app::{App, GRADLE_PROPERTIES, MAX_BACKUPS, closest_game_version, fabric_api_builds_for, maven_metadata_versions, minimum_dependency_version}, bump::{Bump, bump_version, bump_version_text}, common::write_atomic, config::{Config, MappingMode}, doctor::{add_flat_dir_repository, parse_java_version_output}, jdk::{Jdk, find_jdks, pick_jdk}, init::{build_gradle_template, class_name, is_valid_mod_id, is_valid_package, render_template}, journal::{Journal, Operation, Snapshot}, lockfile::{LOCKFILE, Lockfile}, minecraft_version::MinecraftVersion, properties::Properties, ranges::tokenize_range_expression, semantic_version::{SemanticVersion, SemanticVersionRange, VersionRangeSet, simplify_range_set}, state::state_changes, toolchain::{LoomRequirements, gradle_distribution_version, parse_gradle_distribution_url}, workspace::{WORKSPACE_FILE, Workspace}, wrapper::{WRAPPER_JAR, sha256_hex, wrapper_jar_mismatch}};
 
 
 #[test]
@@ -19,12 +19,76 @@ fn version_cmp() {
     ];
     
     for i in 0..(test_versions.len() - 1) {
-        if !(test_versions[i].parse::<SemanticVersion>().unwrap() < test_versions[i + 1].parse::<SemanticVersion>().unwrap()) {
+        if test_versions[i].parse::<SemanticVersion>().unwrap() >= test_versions[i + 1].parse::<SemanticVersion>().unwrap() {
             panic!("{}, {}", test_versions[i], test_versions[i + 1])
         }
     }
 }
 
+#[test]
+fn fabric_version_conformance() {
+    let version = |s: &str| s.parse::<SemanticVersion>().unwrap_or_else(|e| panic!("{}: {}", s, e));
+    
+    for valid in ["1", "1.0", "0.3.5", "1.2.3.4", "1.2.3.4.5.6", "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-0.3.7", "1.0.0-x.7.z.92", "1.0.0-x-y-z.--", "1.0.0+20130313144700", "1.0.0-beta+exp.sha.5114f85", "1.0.0-rc.1+build.1", "1.21.4-SNAPSHOT"] {
+        version(valid);
+    }
+    for invalid in ["", "1.", ".1", "1..2", "a.b", "-1.0", "1.x", "x", "v1.0", "1.0.0-alpha..1", "1.0.0-al$pha", "1.0.0+", "1.0.0+a+b", "1.0.0+build..1", "99999999999.0"] {
+        assert!(invalid.parse::<SemanticVersion>().is_err(), "{} should not parse", invalid);
+    }
+    
+    // Ordering from semver.org, with any number of components and build metadata ignored
+    let ordered = ["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.0.0.1", "1.0.1", "1.9", "1.10", "1.10.0.1-pre", "1.10.0.1"];
+    for pair in ordered.windows(2) {
+        assert!(version(pair[0]) < version(pair[1]), "{} < {}", pair[0], pair[1]);
+    }
+    for (a, b) in [("1", "1.0.0"), ("1.0", "1.0.0.0"), ("1.0.0+a", "1.0.0+b"), ("1.2.3-rc.1+x", "1.2.3-rc.1")] {
+        assert_eq!(version(a), version(b), "{} = {}", a, b);
+        assert_eq!(version(a).to_string().split('+').next(), version(b).to_string().split('+').next());
+    }
+    assert_eq!(version("1.2.3.4").to_string(), "1.2.3.4");
+    assert_eq!(version("1.0.0.4+build").to_string(), "1.0.0.4+build");
+    
+    // Predicates and versions they match and don't match
+    let predicates: [(&str, &[&str], &[&str]); 21] = [
+        ("*", &["0.0.1", "1.0-beta", "99.1"], &[]),
+        ("1.2.3", &["1.2.3", "1.2.3+build.5", "1.2.3.0"], &["1.2.3-beta", "1.2.3.1", "1.2.4"]),
+        ("=1.2.3", &["1.2.3"], &["1.2.4"]),
+        ("1.2.3.4", &["1.2.3.4"], &["1.2.3", "1.2.3.5"]),
+        (">1.2.3", &["1.2.3.1", "1.2.4-beta", "2"], &["1.2.3", "1.2.3+build", "1.2.3-rc.1"]),
+        (">=1.2.3", &["1.2.3", "1.3"], &["1.2.3-rc.1", "1.2.2"]),
+        ("<1.2.3", &["1.2.3-rc.1", "1.2.2", "0.1"], &["1.2.3", "1.2.3.1"]),
+        ("<=1.2.3", &["1.2.3", "1.2.3+build", "1.2"], &["1.2.3.1", "1.2.4-alpha"]),
+        (">=1.0.0-beta.2", &["1.0.0-beta.11", "1.0.0"], &["1.0.0-beta.1", "1.0.0-alpha"]),
+        ("~1.2.3", &["1.2.3", "1.2.9", "1.2.99.1"], &["1.2.2", "1.3.0", "1.3.0-beta"]),
+        ("~1.2", &["1.2", "1.2.9"], &["1.3", "1.1.9"]),
+        ("^1.2.3", &["1.2.3", "1.9.9", "1.99"], &["1.2.2", "2.0.0", "2.0.0-beta"]),
+        ("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2", "1.0"]),
+        ("^0.0.3", &["0.0.3"], &["0.0.4", "0.0.2", "0.1"]),
+        ("^0", &["0", "0.9.9"], &["1.0", "1.0-beta"]),
+        ("1.2.x", &["1.2", "1.2.9", "1.2.0-beta", "1.2.5.1"], &["1.3.0", "1.3.0-beta", "1.1.9"]),
+        ("=1.x", &["1.0", "1.99.1", "1.0-alpha"], &["2.0", "0.9"]),
+        ("1.x.x", &["1.0", "1.99.1"], &["2.0", "0.9"]),
+        ("1.2.3.x", &["1.2.3", "1.2.3.9", "1.2.3.0-beta"], &["1.2.4", "1.2.2.9", "1.2.4.0-alpha"]),
+        ("1.2.x.x", &["1.2", "1.2.9.9"], &["1.3", "1.1.9.9"]),
+        (">=1.2 <1.4", &["1.2", "1.3.9"], &["1.4", "1.1"]),
+    ];
+    for (predicate, matching, other) in predicates {
+        let range = predicate.parse::<SemanticVersionRange>().unwrap_or_else(|e| panic!("{}: {}", predicate, e));
+        for v in matching {
+            assert!(range.contains(&version(v)), "{} should match {}", predicate, v);
+        }
+        for v in other {
+            assert!(!range.contains(&version(v)), "{} should not match {}", predicate, v);
+        }
+        // Written back out, a range matches the same versions
+        let written = range.to_string().parse::<SemanticVersionRange>().unwrap();
+        assert!(matching.iter().chain(other).all(|v| written.contains(&version(v)) == range.contains(&version(v))), "{} written as {}", predicate, range);
+    }
+    for invalid in ["x", "X.1", ">=1.2.x", "~1.x", "^1.x", "<1.x", "1.x.2", "1.2.x.3", "1.x.2.x", "1.2.x-beta", ">=1.a", "~"] {
+        assert!(invalid.parse::<SemanticVersionRange>().is_err(), "{} should not parse", invalid);
+    }
+}
+
 #[test]
 fn version_merging() {
     for range in simplify_range_set(vec![
@@ -43,3 +107,712 @@ fn version_merging() {
         println!("{}", range);
     }
 }
+
+#[test]
+fn version_merging_bounds() {
+    let version = |s: &str| s.parse::<SemanticVersion>().unwrap();
+    let merge = |ranges: &[&str]| simplify_range_set(ranges.iter().map(|s| s.parse().unwrap()).collect()).iter().map(|range| range.to_string()).collect::<Vec<_>>();
+    
+    assert_eq!(merge(&[">=1.14", ">=1.16 <1.17"]), [">=1.14"]);
+    assert_eq!(merge(&[">=1.14 <1.20", ">=1.16 <1.17"]), [">=1.14 <1.20"]);
+    assert_eq!(merge(&["<1.16", ">=1.14 <1.15"]), ["<1.16"]);
+    assert_eq!(merge(&["<1.16", ">=1.15"]), ["*"]);
+    assert_eq!(merge(&[">=1.18 <1.19", ">=1.14 <1.16"]), [">=1.14 <1.16", ">=1.18 <1.19"]);
+    assert_eq!(merge(&[">=1.14 <1.16", ">=1.16 <1.17"]), [">=1.14 <1.17"]);
+    assert_eq!(merge(&[">=1.6.0 <1.5.9"]), Vec::<String>::new());
+    
+    // Every pair of ranges over a small grid, with every combination of bounds
+    let grid = ["1.14", "1.16", "1.18"].map(version);
+    let probes = ["1.13", "1.14", "1.15", "1.16", "1.17", "1.18", "1.19"].map(version);
+    let bounds = std::iter::once(None).chain(grid.iter().cloned().map(Some)).collect::<Vec<_>>();
+    let mut ranges = vec![];
+    for start in &bounds {
+        for end in &bounds {
+            for (start_exclusive, end_inclusive) in [(false, false), (false, true), (true, false), (true, true)] {
+                ranges.push(SemanticVersionRange { start: start.clone(), end: end.clone(), start_exclusive, end_inclusive });
+            }
+        }
+    }
+    for a in &ranges {
+        for b in &ranges {
+            let merged = simplify_range_set(vec![a.clone(), b.clone()]);
+            for probe in &probes {
+                let expected = a.contains(probe) || b.contains(probe);
+                assert_eq!(merged.iter().any(|range| range.contains(probe)), expected, "{} in {} or {}", probe, a, b);
+            }
+            // Merged ranges are sorted and separated by a gap
+            for pair in merged.windows(2) {
+                let (end, start) = (pair[0].end.as_ref().unwrap(), pair[1].start.as_ref().unwrap());
+                assert!(end < start || (end == start && !pair[0].end_inclusive && pair[1].start_exclusive), "{} and {} should merge", pair[0], pair[1]);
+            }
+            assert_eq!(simplify_range_set(merged.clone()), merged);
+        }
+    }
+}
+
+#[test]
+fn range_set_parsing() {
+    let version = |s: &str| s.parse::<SemanticVersion>().unwrap();
+    
+    let set = "[\">=1.14 <1.16.3\", \">=1.18 <1.19\"]".parse::<VersionRangeSet>().unwrap();
+    assert_eq!(set.ranges.len(), 2);
+    assert!(set.contains(&version("1.15.2")) && set.contains(&version("1.18.2")));
+    assert!(!set.contains(&version("1.17.1")) && !set.contains(&version("1.19")));
+    assert_eq!(set.to_string(), "[\">=1.14 <1.16.3\", \">=1.18 <1.19\"]");
+    assert_eq!(set.to_string().parse::<VersionRangeSet>().unwrap(), set);
+    
+    let set = ">=1.14 <1.16.3 || >=1.18 <1.19".parse::<VersionRangeSet>().unwrap();
+    assert_eq!(set.to_string(), "[\">=1.14 <1.16.3\", \">=1.18 <1.19\"]");
+    
+    let set = "1.20 - 1.20.4".parse::<VersionRangeSet>().unwrap();
+    assert!(set.contains(&version("1.20")) && set.contains(&version("1.20.4")));
+    assert!(!set.contains(&version("1.20.5")) && !set.contains(&version("1.19.4")));
+    
+    let set = "\"1.19.2 || 1.20 - 1.20.1\"".parse::<VersionRangeSet>().unwrap();
+    assert!(set.contains(&version("1.19.2")) && set.contains(&version("1.20.1")));
+    assert!(!set.contains(&version("1.19.3")));
+    
+    assert!("[]".parse::<VersionRangeSet>().unwrap().is_empty());
+    assert!("*".parse::<VersionRangeSet>().unwrap().contains(&version("1.21")));
+    assert!("[\">=1.a\"]".parse::<VersionRangeSet>().is_err());
+    
+    // A blank property means nothing is confirmed yet
+    for blank in ["", "  ", "\"\"", "[\"\"]"] {
+        let set = blank.parse::<VersionRangeSet>().unwrap();
+        assert!(set.is_empty() && !set.contains(&version("1.21")), "{:?}", blank);
+    }
+    for invalid in [">=1.20 ||", "|| 1.20", "1.19 || || 1.20", "[\"1.20 ||\"]"] {
+        assert!(invalid.parse::<VersionRangeSet>().is_err(), "{}", invalid);
+    }
+}
+
+/// Small xorshift generator, so the property tests are reproducible without extra dependencies.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+#[test]
+fn range_set_algebra() {
+    let grid = ["1.14", "1.15", "1.16", "1.16.5", "1.17", "1.18", "1.18.2", "1.19", "1.20", "1.21"].map(|s| s.parse::<SemanticVersion>().unwrap());
+    // Versions on the grid and between grid points, to catch open and closed bounds
+    let probes = grid.iter().cloned().chain(["1.13", "1.15.1", "1.16.4", "1.17.1", "1.20.6", "1.22"].map(|s| s.parse::<SemanticVersion>().unwrap())).collect::<Vec<_>>();
+    
+    let mut random = Xorshift(0x2545f4914f6cdd1d);
+    let random_set = |random: &mut Xorshift| {
+        let mut set = VersionRangeSet::default();
+        for _ in 0..random.next(4) {
+            let mut bound = || match random.next(grid.len() + 1) {
+                0 => None,
+                i => Some(grid[i - 1].clone()),
+            };
+            let (start, end) = (bound(), bound());
+            set.insert(SemanticVersionRange { start, end, start_exclusive: random.next(2) == 0, end_inclusive: random.next(2) == 0 });
+        }
+        set
+    };
+    
+    for _ in 0..500 {
+        let (a, b) = (random_set(&mut random), random_set(&mut random));
+        let (union, intersection, difference, complement) = (a.union(&b), a.intersection(&b), a.difference(&b), a.complement());
+        for version in &probes {
+            let (in_a, in_b) = (a.contains(version), b.contains(version));
+            assert_eq!(union.contains(version), in_a || in_b, "{} in {} or {}", version, a, b);
+            assert_eq!(intersection.contains(version), in_a && in_b, "{} in {} and {}", version, a, b);
+            assert_eq!(difference.contains(version), in_a && !in_b, "{} in {} minus {}", version, a, b);
+            assert_eq!(complement.contains(version), !in_a, "{} not in {}", version, a);
+        }
+        assert_eq!(a.complement().complement(), a.union(&a));
+        assert_eq!(union.to_string().parse::<VersionRangeSet>().unwrap().filter(&probes), union.filter(&probes));
+    }
+    
+    let known = VersionRangeSet::from_versions(&grid[2..5]);
+    assert_eq!(known.filter(&probes), grid[2..5].iter().collect::<Vec<_>>());
+    
+    // Complements within the known versions stay bounded by them
+    let confirmed = "[\">=1.16 <=1.17\", \"1.20\"]".parse::<VersionRangeSet>().unwrap();
+    let unconfirmed = confirmed.complement_within(&grid);
+    assert_eq!(unconfirmed.filter(&probes), [&grid[0], &grid[1], &grid[5], &grid[6], &grid[7], &grid[9]]);
+    assert!(unconfirmed.ranges.iter().all(|range| range.start.is_some() && range.end.is_some()), "{}", unconfirmed);
+    assert!(VersionRangeSet::default().complement_within(&[]).is_empty());
+    
+    assert_eq!(tokenize_range_expression("known  minus \">=1.19 <1.21\" or [\"1.14\", \"1.15\"]").unwrap(), ["known", "minus", "\">=1.19 <1.21\"", "or", "[\"1.14\", \"1.15\"]"]);
+    assert!(tokenize_range_expression("not \">=1.19").is_err());
+}
+
+#[test]
+fn range_bounds_and_rendering() {
+    let version = |s: &str| s.parse::<SemanticVersion>().unwrap();
+    let range = |s: &str| s.parse::<SemanticVersionRange>().unwrap();
+    
+    let inclusive = range(">=1.21 <=1.21.4");
+    assert!(inclusive.end_inclusive && inclusive.contains(&version("1.21.4")) && !inclusive.contains(&version("1.21.5")));
+    assert_eq!(inclusive.to_string(), ">=1.21 <=1.21.4");
+    assert_eq!(range(">1.20").to_string(), ">1.20");
+    assert!(!range(">1.20").contains(&version("1.20")) && range(">1.20").contains(&version("1.20.1")));
+    assert_eq!(range("=1.20.1").to_string(), "1.20.1");
+    assert_eq!(range("1.20.x").to_string(), "1.20.x");
+    assert_eq!(range("~1.20").to_string(), ">=1.20 <1.21-");
+    assert_eq!(range(">=1.20 <=1.21.4 <1.21.2").to_string(), ">=1.20 <1.21.2");
+    for s in [">=1.21 <=1.21.4", ">1.20", "1.20.1", "1.20.x", "~1.20", "<=1.19.4"] {
+        assert_eq!(range(s).to_string().parse::<SemanticVersionRange>().unwrap(), range(s));
+    }
+    
+    let known = ["1.19.4", "1.20", "1.20.1", "1.20.2", "1.20.4", "1.20.6", "1.21", "1.21.1", "1.21.3", "1.21.4"].map(version);
+    let compact = |s: &str| s.parse::<VersionRangeSet>().unwrap().compacted(&known).to_string();
+    assert_eq!(compact(">=1.20 <1.21.4"), "[\">=1.20 <=1.21.3\"]");
+    assert_eq!(compact(">=1.20 <1.21"), "[\"1.20.x\"]");
+    assert_eq!(compact("[\"~1.20\", \">=1.21.3\"]"), "[\"1.20.x\", \">=1.21.3 <=1.21.4\"]");
+    assert_eq!(compact(">=1.21.2"), "[\">=1.21.3 <=1.21.4\"]");
+    assert_eq!(compact("[\">=1.20.1 <1.20.4\", \"1.20.4\"]"), "[\">=1.20.1 <=1.20.4\"]");
+    assert_eq!(compact("[\"1.19.4\", \"1.21\"]"), "[\"1.19.4\", \"1.21\"]");
+    // The newest line is still open, so it isn't written as 1.21.x
+    assert_eq!(compact(">=1.21"), "[\">=1.21 <=1.21.4\"]");
+    assert_eq!(compact("<1.19"), "[]");
+}
+
+#[test]
+fn properties_parsing() {
+    let properties = Properties::parse("first=1\n# comment\n! other comment\nspaced  =  2\ncolon:3\nwhitespace 4\ncontinued=a\\\n    b\\\n    c\nescaped\\=key=\\u0041\\tb\\\\\nempty=\n");
+    assert_eq!(properties.get("first").as_deref(), Some("1"));
+    assert_eq!(properties.get("spaced").as_deref(), Some("2"));
+    assert_eq!(properties.get("colon").as_deref(), Some("3"));
+    assert_eq!(properties.get("whitespace").as_deref(), Some("4"));
+    assert_eq!(properties.get("continued").as_deref(), Some("abc"));
+    assert_eq!(properties.get("escaped=key").as_deref(), Some("A\tb\\"));
+    assert_eq!(properties.get("empty").as_deref(), Some(""));
+    assert_eq!(properties.get("comment"), None);
+}
+
+#[test]
+fn properties_editing() {
+    let source = "# header\r\nfirst = 1\r\n\r\ncontinued=a\\\r\n  b\r\nurl=https\\://example.com\r\nlast:2";
+    let mut properties = Properties::parse(source);
+    assert_eq!(properties.to_string(), source);
+    
+    properties.set("first", "one");
+    properties.set("continued", "ab");
+    properties.set("url", "https://example.org");
+    properties.set("new", " x");
+    assert_eq!(properties.to_string(), "# header\r\nfirst = one\r\n\r\ncontinued=ab\r\nurl=https\\://example.org\r\nlast:2\r\nnew=\\ x");
+    
+    properties.set_or_insert_after(0, "inserted", "y");
+    assert_eq!(properties.remove("last").as_deref(), Some("2"));
+    assert_eq!(properties.to_string(), "# header\r\ninserted=y\r\nfirst = one\r\n\r\ncontinued=ab\r\nurl=https\\://example.org\r\nnew=\\ x");
+    
+    // Older ralli versions allowed comments after a value, which are part of it, so they move to their own line when it is replaced
+    let mut properties = Properties::parse("fabric-api=0.106.1+1.21.2\nmidnightlib=1.6.4-fabric # pinned for the config screen\n");
+    assert_eq!(properties.get("midnightlib").as_deref(), Some("1.6.4-fabric # pinned for the config screen"));
+    properties.set("midnightlib", "1.6.5-fabric");
+    properties.set("midnightlib", "1.6.6-fabric");
+    assert_eq!(properties.to_string(), "fabric-api=0.106.1+1.21.2\n# pinned for the config screen\nmidnightlib=1.6.6-fabric\n");
+}
+
+#[test]
+fn journal_undo_redo() {
+    let snapshot = |properties: &str| Snapshot { properties: Some(properties.to_owned()), lockfile: None, mods: vec![], files: vec![] };
+    let mut journal = Journal::default();
+    journal.record(Operation { name: "noop".to_owned(), before: snapshot("a"), after: snapshot("a") });
+    assert!(journal.next_undo().is_none());
+    
+    journal.record(Operation { name: "first".to_owned(), before: snapshot("a"), after: snapshot("b") });
+    journal.record(Operation { name: "second".to_owned(), before: snapshot("b"), after: snapshot("c") });
+    assert_eq!(journal.next_undo().unwrap().name, "second");
+    journal.finish_undo();
+    assert_eq!(journal.next_undo().unwrap().name, "first");
+    assert_eq!(journal.next_redo().unwrap().name, "second");
+    journal.finish_redo();
+    assert_eq!(journal.next_undo().unwrap().name, "second");
+    
+    journal.finish_undo();
+    journal.record(Operation { name: "third".to_owned(), before: snapshot("b"), after: snapshot("d") });
+    assert!(journal.next_redo().is_none());
+}
+
+#[test]
+fn java_version_output() {
+    assert_eq!(parse_java_version_output("openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment"), Some(21));
+    assert_eq!(parse_java_version_output("java version \"1.8.0_292\""), Some(8));
+    assert_eq!(parse_java_version_output("openjdk version \"17-ea\""), Some(17));
+    assert_eq!(parse_java_version_output("command not found"), None);
+}
+
+#[test]
+fn project_templates() {
+    assert_eq!(render_template("{{a}}.{{b}}/{{a}}", &[("a", "x"), ("b", "y")]), "x.y/x");
+    assert_eq!(class_name("my-cool_mod"), "MyCoolMod");
+    assert!(is_valid_mod_id("my_mod-2"));
+    assert!(!is_valid_mod_id("MyMod"));
+    assert!(!is_valid_mod_id("2mod"));
+    assert!(is_valid_package("com.example.my_mod"));
+    assert!(!is_valid_package("com..example"));
+    assert!(!is_valid_package("com.2example"));
+    
+    let example = "sourceSets {\n\t// main.java.exclude(\"com/example/compat/lib/**\")\n}\n\ndependencies {\n\tmodImplementation \"api\"\n\t\n\t// Dependencies of the example mod, which new projects start without\n\tmodImplementation \":lib\"\n}\n";
+    assert_eq!(build_gradle_template(example), "sourceSets {\n\t// main.java.exclude(\"{{package_path}}/compat/lib/**\")\n}\n\ndependencies {\n\tmodImplementation \"api\"\n}\n");
+    // New projects get the build.gradle of the example project, which must keep its dependencies apart
+    let template = build_gradle_template(include_str!("../build.gradle"));
+    assert!(template.contains("fabric-api") && !template.contains(":midnightlib-"));
+}
+
+#[test]
+fn config_defaults_and_overrides() {
+    let config = toml::from_str::<Config>("").unwrap();
+    assert_eq!(config.properties.compatible_range, "minecraft_compatible_range");
+    assert_eq!(config.paths.run_mods, "run/mods");
+    assert_eq!(config.mappings, MappingMode::Yarn);
+    assert!(!config.startup.gradle && !config.startup.stop_daemons);
+    assert_eq!(config.properties.dependency_range("fabric-api"), "fabric_api_version_range");
+    assert!(config.properties.is_ralli_property("midnightlib_version_range"));
+    assert!(config.properties.is_ralli_property("enforce_range"));
+    assert!(!config.properties.is_ralli_property("midnightlib"));
+    
+    let config = toml::from_str::<Config>("mappings = \"mojang\"\n[properties]\ncompatible_range = \"mc_range\"\n[paths]\ngradle = \"./gradlew\"\n[startup]\ngradle = true\n").unwrap();
+    assert_eq!(config.properties.compatible_range, "mc_range");
+    assert_eq!(config.properties.enforce_range, "enforce_range");
+    assert_eq!(config.paths.gradle, "./gradlew");
+    assert_eq!(config.mappings, MappingMode::Mojang);
+    assert!(config.startup.gradle && !config.startup.loom);
+    assert!(!config.release.require_bump);
+    assert_eq!(config.release.tag_prefix, "v");
+    
+    assert!(toml::from_str::<Config>("[paths]\nunknown = 1\n").is_err());
+}
+
+#[test]
+fn workspace_manifest() {
+    let workspace = toml::from_str::<Workspace>("projects = [\"mod-a\", \"../other/mod-b\"]\n").unwrap();
+    assert_eq!(workspace.projects, [std::path::PathBuf::from("mod-a"), std::path::PathBuf::from("../other/mod-b")]);
+    
+    assert!(toml::from_str::<Workspace>("").is_err());
+    assert!(toml::from_str::<Workspace>("projects = []\nname = \"mods\"\n").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn workspace_runs_gradle_in_each_project() {
+    use std::os::unix::fs::PermissionsExt;
+    let folder = std::env::temp_dir().join(format!("ralli-workspace-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join(WORKSPACE_FILE), "projects = [\"mod-a\", \"mod-b\"]\n").unwrap();
+    for project in ["mod-a", "mod-b"] {
+        let project = folder.join(project);
+        std::fs::create_dir_all(&project).unwrap();
+        // Stand-in for gradle that records the folder it ran in
+        let gradle = project.join("gradlew.sh");
+        std::fs::write(&gradle, "#!/bin/sh\npwd > ran-in\n").unwrap();
+        std::fs::set_permissions(&gradle, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(project.join("ralli.toml"), "[paths]\ngradle = \"./gradlew.sh\"\n").unwrap();
+    }
+    
+    let mut app = App::new().unwrap();
+    app.cwd = folder.clone();
+    let results = app.for_each_project(|app| app.run_gradle_status(["build"])).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, result)| matches!(result, Ok(true))));
+    for project in ["mod-a", "mod-b"] {
+        let ran_in = std::fs::read_to_string(folder.join(project).join("ran-in")).unwrap();
+        assert_eq!(std::path::Path::new(ran_in.trim()).canonicalize().unwrap(), folder.join(project).canonicalize().unwrap());
+    }
+    assert!(!std::fs::exists(folder.join("ran-in")).unwrap());
+    assert_eq!(app.cwd, folder);
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn mojang_version_manifest() {
+    let manifest = serde_json::from_str::<VersionManifest>(r#"{"latest": {"release": "1.21.4", "snapshot": "25w02a"}, "versions": [{"id": "1.21.4", "type": "release", "url": "https://piston-meta.mojang.com/v1/packages/a3bcba436caa849622fd7e1e5b89489ed6c9ac63/1.21.4.json", "time": "2024-12-03T10:24:48+00:00", "releaseTime": "2024-12-03T10:12:57+00:00", "sha1": "a3bcba436caa849622fd7e1e5b89489ed6c9ac63", "complianceLevel": 1}]}"#).unwrap();
+    assert_eq!(manifest.versions[0].id, "1.21.4");
+    assert_eq!(manifest.versions[0].releaseTime, "2024-12-03T10:12:57+00:00");
+    
+    let details = serde_json::from_str::<VersionDetails>(r#"{"id": "1.21.4", "javaVersion": {"component": "java-runtime-delta", "majorVersion": 21}, "type": "release"}"#).unwrap();
+    assert_eq!(details.javaVersion.map(|java| java.majorVersion), Some(21));
+    assert!(serde_json::from_str::<VersionDetails>(r#"{"id": "1.2.5"}"#).unwrap().javaVersion.is_none());
+}
+
+#[test]
+fn minecraft_versions() {
+    let version = |s: &str| s.parse::<MinecraftVersion>().unwrap();
+    let semver = |s: &str| s.parse::<SemanticVersion>().unwrap();
+    
+    assert_eq!(version("1.21").semver, semver("1.21.0"));
+    assert_eq!(version("1.21"), version("1.21.0"));
+    assert_eq!(version("1.21").to_string(), "1.21");
+    assert_eq!(version("1.21.0").to_string(), "1.21.0");
+    assert_eq!(version("1.21-pre1").semver, semver("1.21.0-beta.1"));
+    assert_eq!(version("1.21-rc1").semver, semver("1.21.0-rc.1"));
+    assert_eq!(version("1.21-pre1").to_string(), "1.21-pre1");
+    assert!(!version("1.21-rc1").is_release() && version("1.21").is_release());
+    assert_eq!(MinecraftVersion::new("24w14a", Some("1.20.5")).unwrap().semver, semver("1.20.5-alpha.24.14.a"));
+    assert!("24w14a".parse::<MinecraftVersion>().is_err());
+    assert!("1.21-foo".parse::<MinecraftVersion>().is_err());
+    assert!("Beta 1.7.3".parse::<MinecraftVersion>().is_err());
+    
+    // Without release times the normalized versions are compared, which puts prereleases before their release
+    let mut versions = ["1.21", "1.21-rc1", "1.20.6", "1.21-pre2", "1.21-pre10"].map(version);
+    versions.sort();
+    assert_eq!(versions.map(|v| v.to_string()), ["1.20.6", "1.21-pre2", "1.21-pre10", "1.21-rc1", "1.21"]);
+    
+    // Release times decide between versions semver gets wrong, like a snapshot released after a patch of an older line
+    let timed = |id: &str, next_release: Option<&str>, time: &str| MinecraftVersion { release_time: Some(time.to_owned()), ..MinecraftVersion::new(id, next_release).unwrap() };
+    let snapshot = timed("24w14a", Some("1.20.5"), "2024-04-03T12:00:00+00:00");
+    let release = timed("1.20.4", None, "2023-12-07T12:00:00+00:00");
+    assert!(release < snapshot);
+    let old_line = timed("1.20.6", None, "2024-04-29T12:00:00+00:00");
+    let new_line = timed("1.21-pre1", None, "2024-05-29T12:00:00+00:00");
+    assert!(old_line < new_line && snapshot < old_line);
+    
+    // A mix of timed and untimed versions still sorts consistently, untimed ones last
+    let mut versions = vec![new_line.clone(), version("1.19.4"), old_line.clone(), version("1.21.1"), snapshot.clone(), release.clone(), version("1.21-rc1")];
+    versions.sort();
+    assert_eq!(versions.iter().map(|v| v.to_string()).collect::<Vec<_>>(), ["1.20.4", "24w14a", "1.20.6", "1.21-pre1", "1.19.4", "1.21-rc1", "1.21.1"]);
+    for a in &versions {
+        for b in &versions {
+            assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{} and {}", a, b);
+            for c in &versions {
+                if a < b && b < c { assert!(a < c, "{} < {} < {}", a, b, c); }
+            }
+        }
+    }
+    
+    // Non-ASCII ids that look like weekly snapshots are rejected rather than cut mid character
+    assert!(MinecraftVersion::new("24w1é", Some("1.20.5")).is_err());
+    assert!(MinecraftVersion::new("24wé1", Some("1.20.5")).is_err());
+}
+
+#[test]
+fn serde_versions_and_project_state() {
+    let range = ">=1.21 <=1.21.4".parse::<SemanticVersionRange>().unwrap();
+    assert_eq!(serde_json::to_string(&range).unwrap(), r#"">=1.21 <=1.21.4""#);
+    assert_eq!(serde_json::from_str::<SemanticVersionRange>(r#"">=1.21 <=1.21.4""#).unwrap(), range);
+    assert_eq!(serde_json::to_string(&"1.2.3-beta.1+build.4".parse::<SemanticVersion>().unwrap()).unwrap(), r#""1.2.3-beta.1+build.4""#);
+    assert!(serde_json::from_str::<SemanticVersion>(r#""1.a""#).is_err());
+    assert_eq!(serde_json::to_string(&"1.21".parse::<MinecraftVersion>().unwrap()).unwrap(), r#""1.21""#);
+    assert_eq!(serde_json::from_str::<MinecraftVersion>(r#""1.21-pre1""#).unwrap().id, "1.21-pre1");
+    
+    let set = "[\">=1.14 <1.16.3\", \"1.18.2\"]".parse::<VersionRangeSet>().unwrap();
+    assert_eq!(serde_json::to_string(&set).unwrap(), r#"[">=1.14 <1.16.3","1.18.2"]"#);
+    assert_eq!(serde_json::from_str::<VersionRangeSet>(&serde_json::to_string(&set).unwrap()).unwrap(), set);
+    assert_eq!(serde_json::from_str::<VersionRangeSet>(r#"">=1.14 <1.16.3 || 1.18.2""#).unwrap(), set.simplified());
+    
+    let folder = std::env::temp_dir().join(format!("ralli-state-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join(GRADLE_PROPERTIES), "mod_version=1.2.3
+
+# Ralli driven values
+minecraft_compatible_range=[\">=1.21.2 <=1.21.3\"]
+enforce_range=true
+minecraft_version=1.21.2
+yarn_mappings=1.21.2+build.1
+java_version=21
+disabled_dependencies=
+fabric_api_version_range=\">=0.106.1+1.21.2\"
+midnightlib_version_range=\">=1.6.4-fabric\"
+
+# Dependencies
+fabric-api=0.106.1+1.21.2
+midnightlib=1.6.4-fabric
+").unwrap();
+    let mut app = App::new().unwrap();
+    app.cwd = folder.clone();
+    assert_eq!(app.load_project_state().unwrap(), None);
+    
+    let state = app.project_state(&app.read_properties().unwrap()).unwrap();
+    assert_eq!(state.minecraft_version.to_string(), "1.21.2");
+    assert_eq!((state.yarn_build, state.java_version, state.enforce_range), (Some(1), 21, true));
+    assert!(state.compatible_ranges.contains(&"1.21.3".parse().unwrap()) && !state.compatible_ranges.contains(&"1.21.4".parse().unwrap()));
+    assert_eq!(state.dependencies.iter().map(|d| (d.name.as_str(), d.version.as_str())).collect::<Vec<_>>(), [("fabric-api", "0.106.1+1.21.2"), ("midnightlib", "1.6.4-fabric")]);
+    assert!(state.dependencies[0].range.as_ref().unwrap().contains(&"0.107.0+1.21.3".parse().unwrap()));
+    
+    app.save_project_state(&state).unwrap();
+    assert_eq!(app.load_project_state().unwrap().as_ref(), Some(&state));
+    
+    // Hand edits show up against the saved state
+    assert!(state_changes(&state, &state).is_empty());
+    let mut properties = app.read_properties().unwrap();
+    properties.set("java_version", "17");
+    properties.set("midnightlib", "1.6.5-fabric");
+    let current = app.project_state(&properties).unwrap();
+    assert_eq!(state_changes(&state, &current), ["Java 21 -> 17", "midnightlib 1.6.4-fabric -> 1.6.5-fabric"]);
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn jdk_discovery() {
+    let folder = std::env::temp_dir().join(format!("ralli-jdks-{}", std::process::id()));
+    for (name, release) in [("jdk-17", "JAVA_VERSION=\"17.0.9\""), ("jdk8u392", "JAVA_VERSION=\"1.8.0_392\""), ("jdk-21", "JAVA_VERSION=\"21.0.2\"")] {
+        std::fs::create_dir_all(folder.join(name).join("bin")).unwrap();
+        std::fs::write(folder.join(name).join("bin").join(if cfg!(windows) { "java.exe" } else { "java" }), "").unwrap();
+        std::fs::write(folder.join(name).join("release"), format!("IMPLEMENTOR=\"Eclipse Adoptium\"\n{}\n", release)).unwrap();
+    }
+    std::fs::create_dir_all(folder.join("not-a-jdk")).unwrap();
+    
+    let folder_path = folder.canonicalize().unwrap();
+    let mut found = find_jdks(&[folder.to_string_lossy().into_owned()]).into_iter().filter(|jdk| jdk.home.starts_with(&folder_path)).map(|jdk| jdk.major_version).collect::<Vec<_>>();
+    found.sort();
+    assert_eq!(found, [8, 17, 21]);
+    std::fs::remove_dir_all(&folder).unwrap();
+    
+    let jdks = [17, 21, 8].map(|major_version| Jdk { home: format!("jdk-{}", major_version).into(), major_version });
+    assert_eq!(pick_jdk(&jdks, 17).map(|jdk| jdk.major_version), Some(17));
+    assert_eq!(pick_jdk(&jdks, 16).map(|jdk| jdk.major_version), Some(17));
+    assert_eq!(pick_jdk(&jdks, 25), None);
+}
+
+#[test]
+fn loom_compatibility_matrix() {
+    let version = |s: &str| s.parse::<SemanticVersion>().unwrap();
+    assert_eq!(parse_gradle_distribution_url("https://services.gradle.org/distributions/gradle-8.10.2-bin.zip").map(|v| v.to_string()), Some("8.10.2".to_owned()));
+    assert_eq!(parse_gradle_distribution_url("https://services.gradle.org/distributions/gradle-9.0-all.zip").map(|v| v.to_string()), Some("9".to_owned()));
+    assert!(parse_gradle_distribution_url("https://example.com/gradle.zip").is_none());
+    
+    let mut app = App::new().unwrap();
+    assert!(app.check_toolchain(&version("8.10.2"), &version("1.8-SNAPSHOT"), &version("1.21.1")).is_ok());
+    assert!(app.check_toolchain(&version("8.5"), &version("1.8-SNAPSHOT"), &version("1.21.1")).is_err());
+    assert!(app.check_toolchain(&version("8.5"), &version("1.5-SNAPSHOT"), &version("1.20.6")).is_err());
+    assert!(app.check_toolchain(&version("1.0"), &version("0.12-SNAPSHOT"), &version("1.16.5")).is_ok());
+    assert_eq!(app.pick_loom(&version("8.10.2"), &version("1.21.1")).unwrap().as_deref(), Some("1.8-SNAPSHOT"));
+    assert_eq!(app.pick_loom(&version("8.5"), &version("1.20.6")).unwrap(), None);
+    // Newer gradle versions aren't refused without a known upper bound
+    assert!(app.check_toolchain(&version("9.1"), &version("1.11-SNAPSHOT"), &version("1.21.8")).is_ok());
+    assert!(app.check_toolchain(&version("9.1"), &version("1.0-SNAPSHOT"), &version("1.20.1")).is_ok());
+    
+    app.config.loom_compatibility.push(LoomRequirements { loom: "1.8".to_owned(), gradle: ">=8.9 <8.11".to_owned(), minecraft: ">=1.14".to_owned() });
+    assert!(app.check_toolchain(&version("8.9"), &version("1.8-SNAPSHOT"), &version("1.21.1")).is_ok());
+    assert_eq!(app.pick_loom(&version("8.9"), &version("1.21.1")).unwrap().as_deref(), Some("1.8-SNAPSHOT"));
+}
+
+#[test]
+fn gradle_checksums() {
+    assert_eq!(sha256_hex("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(sha256_hex([]), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    
+    // A jar from another gradle version than the one in use is still official
+    let published = [("8.10", "aaa"), ("8.11", "bbb"), ("8.11.1", "bbb")].map(|(version, checksum)| (version.to_owned(), checksum.to_owned())).into();
+    assert_eq!(wrapper_jar_mismatch("gradle-wrapper.jar", "aaa", &published), None);
+    assert_eq!(wrapper_jar_mismatch("gradle-wrapper.jar", "bbb", &published), None);
+    assert_eq!(wrapper_jar_mismatch("gradle-wrapper.jar", "ccc", &published).unwrap(), "gradle-wrapper.jar has checksum ccc, which matches none of the 3 published gradle wrappers.");
+    assert_eq!(gradle_distribution_version("https://services.gradle.org/distributions/gradle-9.0.0-bin.zip"), Some("9.0.0"));
+    assert_eq!(gradle_distribution_version("https://services.gradle.org/distributions/gradle-8.14-rc-1-all.zip"), Some("8.14-rc-1"));
+}
+
+#[test]
+fn lockfile_yarn_pins() {
+    let folder = std::env::temp_dir().join(format!("ralli-lockfile-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    assert!(Lockfile::load(&folder).unwrap().yarn_pins.is_empty());
+    
+    std::fs::write(folder.join(LOCKFILE), r#"{"minecraft_version": "1.21.1", "dependencies": []}"#).unwrap();
+    let mut lockfile = Lockfile::load(&folder).unwrap();
+    assert_eq!(lockfile.minecraft_version, "1.21.1");
+    assert!(lockfile.yarn_pins.is_empty());
+    
+    lockfile.yarn_pins.insert("1.21.1".to_owned(), 2);
+    lockfile.write(&folder).unwrap();
+    assert_eq!(Lockfile::load(&folder).unwrap().yarn_pins.get("1.21.1"), Some(&2));
+    
+    // The dependency version for the oldest confirmed Minecraft version is kept for the next run
+    std::fs::write(folder.join(LOCKFILE), r#"{"minecraft_version": "1.21.4", "dependencies": [{"name": "fabric-api", "version": "0.110.0+1.21.4", "oldest_confirmed": ["1.21.1", "0.105.0+1.21.1"]}]}"#).unwrap();
+    let lockfile = Lockfile::load(&folder).unwrap();
+    assert_eq!(lockfile.dependencies[0].oldest_confirmed, Some(("1.21.1".to_owned(), "0.105.0+1.21.1".to_owned())));
+    std::fs::remove_dir_all(&folder).unwrap();
+    
+    assert_eq!(minimum_dependency_version("0.110.0+1.21.4", Some("0.105.0+1.21.1")).unwrap().to_string(), "0.105+1.21.1");
+    assert_eq!(minimum_dependency_version("1.6.4", Some("1.7.0")).unwrap().to_string(), "1.6.4");
+    assert_eq!(minimum_dependency_version("1.6.4", None).unwrap().to_string(), "1.6.4");
+    assert_eq!(minimum_dependency_version("1.6.4", Some("not semver")).unwrap().to_string(), "1.6.4");
+    assert_eq!(minimum_dependency_version("v1-build", Some("1.0")), None);
+}
+
+#[test]
+fn mod_version_bumps() {
+    let bump = |version: &str, bump: &str| bump_version(&version.parse().unwrap(), &bump.parse().unwrap()).map(|version| version.to_string());
+    assert_eq!(bump("1.2.3", "patch").unwrap(), "1.2.4");
+    assert_eq!(bump("1.2.3", "minor").unwrap(), "1.3");
+    assert_eq!(bump("1.2.3", "major").unwrap(), "2");
+    assert_eq!(bump("1.2.3.4", "patch").unwrap(), "1.2.4");
+    assert_eq!(bump("1.2.3+build.5", "patch").unwrap(), "1.2.4");
+    
+    // Prereleases bump to the release they lead up to
+    assert_eq!(bump("1.2.3-beta.2", "patch").unwrap(), "1.2.3");
+    assert_eq!(bump("1.3.0-beta.2", "minor").unwrap(), "1.3");
+    assert_eq!(bump("1.3.1-beta.2", "minor").unwrap(), "1.4");
+    assert_eq!(bump("2.0.0-rc.1", "major").unwrap(), "2");
+    assert_eq!(bump("2.1.0-rc.1", "major").unwrap(), "3");
+    
+    assert_eq!(bump("1.2.3", "pre beta").unwrap(), "1.2.4-beta.1");
+    assert_eq!(bump("1.2.4-beta.1", "pre beta").unwrap(), "1.2.4-beta.2");
+    assert_eq!(bump("1.2.4-beta.9", "pre beta").unwrap(), "1.2.4-beta.10");
+    assert_eq!(bump("1.2.4-beta", "pre beta").unwrap(), "1.2.4-beta.1");
+    assert_eq!(bump("1.2.4-beta.2", "pre rc").unwrap(), "1.2.4-rc.1");
+    assert!(bump("1.2.4-rc.1", "pre beta").is_err());
+    
+    // Written versions keep their number of components
+    assert_eq!(bump_version_text("1.2.3", &Bump::Minor).unwrap(), "1.3.0");
+    assert_eq!(bump_version_text("1.2", &Bump::Major).unwrap(), "2.0");
+    assert_eq!(bump_version_text("1.2", &Bump::Patch).unwrap(), "1.2.1");
+    assert_eq!(bump_version_text("1.2.3.4", &Bump::Pre("beta".to_owned())).unwrap(), "1.2.4.0-beta.1");
+    assert_eq!(bump_version_text("1.2.4-beta.1+build.7", &Bump::Pre("beta".to_owned())).unwrap(), "1.2.4-beta.2");
+    
+    assert_eq!("pre alpha".parse::<Bump>().unwrap(), Bump::Pre("alpha".to_owned()));
+    for invalid in ["", "pre", "pre be_ta", "patch 2", "huge"] {
+        assert!(invalid.parse::<Bump>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn fallback_game_versions() {
+    let closest = |version: &str, supported: &[&str]| {
+        let supported = supported.iter().map(|s| s.parse::<MinecraftVersion>().unwrap()).collect::<Vec<_>>();
+        closest_game_version(&version.parse().unwrap(), &supported).map(|v| v.to_string())
+    };
+    // The nearest patch of the same minor line, the earlier one on a tie
+    assert_eq!(closest("1.20.3", &["1.20.1", "1.20.4", "1.19.4"]).as_deref(), Some("1.20.4"));
+    assert_eq!(closest("1.20.3", &["1.20.4", "1.20.2"]).as_deref(), Some("1.20.2"));
+    assert_eq!(closest("1.20.3", &["1.20.5", "1.20.1"]).as_deref(), Some("1.20.1"));
+    assert_eq!(closest("1.20.3", &["1.20.6", "1.20.1"]).as_deref(), Some("1.20.1"));
+    assert_eq!(closest("1.20.3", &["1.20.6", "1.21", "1.19.4"]).as_deref(), Some("1.20.6"));
+    
+    // Otherwise the closest earlier version, never a later one
+    assert_eq!(closest("1.20.3", &["1.19.2", "1.19.4", "1.21"]).as_deref(), Some("1.19.4"));
+    assert_eq!(closest("1.20.3", &["1.21", "1.21.1"]), None);
+    assert_eq!(closest("1.20.3", &[]), None);
+}
+
+#[test]
+fn fabric_api_maven_metadata() {
+    let metadata = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.fabricmc.fabric-api</groupId>
+  <artifactId>fabric-api</artifactId>
+  <versioning>
+    <latest>0.110.0+1.21.4</latest>
+    <release>0.110.0+1.21.4</release>
+    <versions>
+      <version>0.92.2+1.20.1</version>
+      <version>0.102.0+1.21</version>
+      <version>0.102.0+1.21.1</version>
+      <version>0.105.0+1.21.1</version>
+      <version> 0.106.1+1.21.1 </version>
+      <version>0.110.0+1.21.4</version>
+    </versions>
+    <lastUpdated>20241203103212</lastUpdated>
+  </versioning>
+</metadata>
+"#;
+    let builds = maven_metadata_versions(metadata);
+    assert_eq!(builds.len(), 6);
+    assert_eq!(builds[0], "0.92.2+1.20.1");
+    assert_eq!(builds[4], "0.106.1+1.21.1");
+    
+    let version = |s: &str| s.parse::<MinecraftVersion>().unwrap();
+    assert_eq!(fabric_api_builds_for(&builds, &version("1.21.1")), ["0.106.1+1.21.1", "0.105.0+1.21.1", "0.102.0+1.21.1"]);
+    // 1.21 is a suffix of neither 1.21.1 nor 1.21.4 builds
+    assert_eq!(fabric_api_builds_for(&builds, &version("1.21")), ["0.102.0+1.21"]);
+    assert!(fabric_api_builds_for(&builds, &version("1.20.4")).is_empty());
+    assert!(maven_metadata_versions("<metadata></metadata>").is_empty());
+}
+
+#[test]
+fn atomic_writes_and_backups() {
+    let folder = std::env::temp_dir().join(format!("ralli-backups-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    
+    let path = folder.join("file.txt");
+    write_atomic(&path, "first").unwrap();
+    write_atomic(&path, "second").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+    // Only the written file is left, not the temporary one it was renamed from
+    assert_eq!(std::fs::read_dir(&folder).unwrap().count(), 1);
+    assert!(write_atomic(folder.join(".."), "contents").is_err());
+    
+    let mut app = App::new().unwrap();
+    app.cwd = folder.clone();
+    let write = |i: usize| app.write_properties(&Properties::parse(&format!("value={}\n", i))).unwrap();
+    let read = |path: &std::path::Path| std::fs::read_to_string(path).unwrap();
+    for i in 0..MAX_BACKUPS + 5 {
+        write(i);
+    }
+    // Writing the same contents again makes no backup
+    write(MAX_BACKUPS + 4);
+    
+    // The first write had nothing to back up, the oldest backups beyond the limit were pruned
+    let backups = app.list_backups().unwrap();
+    assert_eq!(backups.len(), MAX_BACKUPS);
+    for (age, backup) in backups.iter().enumerate() {
+        assert_eq!(read(backup), format!("value={}\n", MAX_BACKUPS + 3 - age), "{}", backup.display());
+    }
+    
+    app.restore_backup(&backups[2]).unwrap();
+    assert_eq!(read(&folder.join(GRADLE_PROPERTIES)), format!("value={}\n", MAX_BACKUPS + 1));
+    // The contents before the restore were backed up, so it can be undone
+    let backups = app.list_backups().unwrap();
+    assert_eq!(backups.len(), MAX_BACKUPS);
+    assert_eq!(read(&backups[0]), format!("value={}\n", MAX_BACKUPS + 4));
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn flat_dir_repository_fix() {
+    let flat_dir = "\tflatDir {\n\t\tdirs \"local_maven\"\n\t}\n";
+    // The publishing block comes first, but the top level repositories block is the one that finds dependencies
+    let build = "publishing {\n\trepositories {\n\t}\n}\n\nrepositories {\n\tmavenCentral()\n}\n\ndependencies {\n}\n";
+    assert_eq!(add_flat_dir_repository(build, "local_maven"), format!("publishing {{\n\trepositories {{\n\t}}\n}}\n\nrepositories {{\n{}\tmavenCentral()\n}}\n\ndependencies {{\n}}\n", flat_dir));
+    
+    let build = "plugins {\n}\n\ndependencies {\n}\n\npublishing {\n\trepositories {\n\t}\n}\n";
+    assert_eq!(add_flat_dir_repository(build, "local_maven"), format!("plugins {{\n}}\n\nrepositories {{\n{}}}\n\ndependencies {{\n}}\n\npublishing {{\n\trepositories {{\n\t}}\n}}\n", flat_dir));
+    
+    assert_eq!(add_flat_dir_repository("plugins {\n}\n", "local_maven"), format!("plugins {{\n}}\n\nrepositories {{\n{}}}\n", flat_dir));
+}
+
+#[test]
+fn undo_restores_project_files() {
+    let folder = std::env::temp_dir().join(format!("ralli-undo-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::write(folder.join("build.gradle"), "repositories {\n}\n").unwrap();
+    let mut app = App::new().unwrap();
+    app.cwd = folder.clone();
+    let wrapper_properties = folder.join(&app.config.paths.gradle_wrapper_properties);
+    
+    app.record("doctor fix", |app| {
+        write_atomic(app.cwd.join("build.gradle"), "repositories {\n\tflatDir {\n\t}\n}\n")?;
+        std::fs::create_dir_all(wrapper_properties.parent().unwrap())?;
+        write_atomic(&wrapper_properties, "distributionUrl=\n")
+    }).unwrap();
+    app.undo().unwrap();
+    assert_eq!(std::fs::read_to_string(folder.join("build.gradle")).unwrap(), "repositories {\n}\n");
+    assert!(!wrapper_properties.exists());
+    
+    app.redo().unwrap();
+    assert_eq!(std::fs::read_to_string(folder.join("build.gradle")).unwrap(), "repositories {\n\tflatDir {\n\t}\n}\n");
+    assert_eq!(std::fs::read_to_string(&wrapper_properties).unwrap(), "distributionUrl=\n");
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn doctor_needs_wrapper_jar_first() {
+    let folder = std::env::temp_dir().join(format!("ralli-doctor-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let mut app = App::new().unwrap();
+    app.cwd = folder.clone();
+    // Outside of a project the jar isn't what's missing
+    assert!(app.diagnose().iter().all(|problem| !problem.description.contains(WRAPPER_JAR)));
+    
+    std::fs::write(folder.join(GRADLE_PROPERTIES), "mod_version=1.0.0\n").unwrap();
+    let problems = app.diagnose();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].description.starts_with(WRAPPER_JAR));
+    
+    let jar = app.wrapper_jar_path();
+    std::fs::create_dir_all(jar.parent().unwrap()).unwrap();
+    std::fs::write(&jar, "").unwrap();
+    let problems = app.diagnose();
+    assert!(problems.len() > 1 && problems.iter().all(|problem| !problem.description.contains(WRAPPER_JAR)));
+    std::fs::remove_dir_all(&folder).unwrap();
+}
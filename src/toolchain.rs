@@ -0,0 +1,157 @@
+use serde::Deserialize;
+
+use crate::{app::App, common::*, minecraft_version::MinecraftVersion, properties::Properties, semantic_version::{SemanticVersion, SemanticVersionRange}};
+
+/// Known gradle and Minecraft version requirements of each loom release line, as `(loom, gradle, minecraft)`.
+/// Gradle only has a lower bound, the oldest version each line runs on. The only Minecraft bound is that lines before 1.6
+/// can't build 1.20.5 and newer, which Fabric's announcement for 1.20.5 says needs loom 1.6.
+/// Lines not listed here, and newer gradle versions, are assumed to work. Add upper bounds in `ralli.toml` where one is known to break.
+pub const LOOM_COMPATIBILITY: [(&str, &str, &str); 14] = [
+    ("1.1", ">=8.0", "<1.20.5"),
+    ("1.2", ">=8.1", "<1.20.5"),
+    ("1.3", ">=8.2", "<1.20.5"),
+    ("1.4", ">=8.3", "<1.20.5"),
+    ("1.5", ">=8.5", "<1.20.5"),
+    ("1.6", ">=8.6", "*"),
+    ("1.7", ">=8.8", "*"),
+    ("1.8", ">=8.10", "*"),
+    ("1.9", ">=8.11", "*"),
+    ("1.10", ">=8.12", "*"),
+    ("1.11", ">=8.14", "*"),
+    ("1.12", ">=9.0", "*"),
+    ("1.13", ">=9.1", "*"),
+    ("1.14", ">=9.2", "*"),
+];
+
+
+/// Gradle and Minecraft versions a loom release line works with. Entries in `ralli.toml` take precedence over the built-in ones.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LoomRequirements {
+    /// Loom release line, like `1.7`.
+    pub loom: String,
+    /// Version range of gradle the line works with, like `>=8.8 <9`.
+    pub gradle: String,
+    /// Version range of Minecraft the line can build for.
+    pub minecraft: String,
+}
+
+impl LoomRequirements {
+    pub fn matches_loom(&self, loom: &SemanticVersion) -> bool {
+        self.loom.parse::<SemanticVersion>().is_ok_and(|line| line.major == loom.major && line.minor == loom.minor)
+    }
+    
+    pub fn supports_gradle(&self, gradle: &SemanticVersion) -> Result<bool> {
+        Ok(self.gradle.parse::<SemanticVersionRange>()?.contains(gradle))
+    }
+    
+    pub fn supports_minecraft(&self, minecraft: &SemanticVersion) -> Result<bool> {
+        Ok(self.minecraft.parse::<SemanticVersionRange>()?.contains(minecraft))
+    }
+}
+
+/// Gradle version of a wrapper distribution url as written, like `8.10.2` for `https://services.gradle.org/distributions/gradle-8.10.2-bin.zip`.
+pub fn gradle_distribution_version(url: &str) -> Option<&str> {
+    let file_name = url.rsplit('/').next()?.strip_prefix("gradle-")?;
+    file_name.strip_suffix("-bin.zip").or(file_name.strip_suffix("-all.zip"))
+}
+
+pub fn parse_gradle_distribution_url(url: &str) -> Option<SemanticVersion> {
+    gradle_distribution_version(url)?.parse().ok()
+}
+
+impl App {
+    /// Loom compatibility matrix, with the entries from `ralli.toml` first.
+    pub fn loom_compatibility(&self) -> Vec<LoomRequirements> {
+        self.config.loom_compatibility.iter().cloned().chain(LOOM_COMPATIBILITY.iter().map(|(loom, gradle, minecraft)| LoomRequirements {
+            loom: loom.to_string(),
+            gradle: gradle.to_string(),
+            minecraft: minecraft.to_string(),
+        })).collect()
+    }
+    
+    fn loom_requirements(&self, loom: &SemanticVersion) -> Option<LoomRequirements> {
+        self.loom_compatibility().into_iter().find(|requirements| requirements.matches_loom(loom))
+    }
+    
+    /// Gradle version the wrapper downloads.
+    pub fn current_gradle_version(&self) -> Result<SemanticVersion> {
+        let path = self.cwd.join(&self.config.paths.gradle_wrapper_properties);
+        let url = Properties::parse(&std::fs::read_to_string(&path)?).get("distributionUrl").ok_or("Could not find location of active gradle source.")?;
+        parse_gradle_distribution_url(&url).ok_or(format!("Could not get the gradle version from '{}'.", url).into())
+    }
+    
+    /// Check a combination of gradle, loom and Minecraft versions against the compatibility matrix.
+    pub fn check_toolchain(&self, gradle: &SemanticVersion, loom: &SemanticVersion, minecraft: &SemanticVersion) -> Result<()> {
+        let Some(requirements) = self.loom_requirements(loom) else { return Ok(()) };
+        if !requirements.supports_gradle(gradle)? {
+            return Err(format!("Loom {} needs gradle {}, not {}.", requirements.loom, requirements.gradle, gradle).into())
+        }
+        if !requirements.supports_minecraft(minecraft)? {
+            return Err(format!("Loom {} supports Minecraft {}, not {}.", requirements.loom, requirements.minecraft, minecraft).into())
+        }
+        Ok(())
+    }
+    
+    /// Check a gradle version against the current loom version and the Minecraft versions being built.
+    /// A project without a readable loom version has nothing to check against.
+    pub fn check_gradle_update(&self, gradle: &SemanticVersion) -> Result<()> {
+        let Ok(properties) = self.read_properties() else { return Ok(()) };
+        let Some(loom) = properties.get(&self.config.properties.loom_version).and_then(|s| s.parse::<SemanticVersion>().ok()) else { return Ok(()) };
+        for minecraft in self.built_minecraft_versions(&properties)? {
+            self.check_toolchain(gradle, &loom, &minecraft)?;
+        }
+        Ok(())
+    }
+    
+    /// Check a loom version against the current gradle version and the Minecraft versions being built.
+    pub fn check_loom_update(&self, properties: &Properties, loom: &SemanticVersion) -> Result<()> {
+        let gradle = self.current_gradle_version()?;
+        for minecraft in self.built_minecraft_versions(properties)? {
+            self.check_toolchain(&gradle, loom, &minecraft)?;
+        }
+        Ok(())
+    }
+    
+    /// The Minecraft version being tested and the oldest confirmed one, which a toolchain update must keep working.
+    fn built_minecraft_versions(&self, properties: &Properties) -> Result<Vec<SemanticVersion>> {
+        let mut versions = vec![self.find_property(properties, &self.config.properties.minecraft_version)?.parse::<MinecraftVersion>()?.semver];
+        if let Some(oldest) = self.parse_current_ranges(properties)?.ranges.into_iter().filter_map(|range| range.start).min() {
+            versions.push(oldest);
+        }
+        Ok(versions)
+    }
+    
+    /// Newest loom line that works with the current gradle version and a Minecraft version, as a `loom_version` value.
+    pub fn pick_loom(&self, gradle: &SemanticVersion, minecraft: &SemanticVersion) -> Result<Option<String>> {
+        let mut seen: Vec<SemanticVersion> = vec![];
+        let mut best: Option<SemanticVersion> = None;
+        for requirements in self.loom_compatibility() {
+            let Ok(line) = requirements.loom.parse::<SemanticVersion>() else { continue };
+            // Entries from ralli.toml shadow the built-in ones for the same line
+            if seen.iter().any(|seen| requirements.matches_loom(seen)) { continue }
+            seen.push(line.clone());
+            if requirements.supports_gradle(gradle)? && requirements.supports_minecraft(minecraft)? && best.as_ref().is_none_or(|best| line > *best) {
+                best = Some(line);
+            }
+        }
+        Ok(best.map(|line| format!("{}.{}-SNAPSHOT", line.major, line.minor)))
+    }
+    
+    /// Switch `loom_version` to a loom line that can build `minecraft`, if the current one can't.
+    pub fn select_loom(&self, properties: &mut Properties, minecraft: &SemanticVersion) -> Result<()> {
+        let names = &self.config.properties;
+        let loom = self.find_property(properties, &names.loom_version)?.parse::<SemanticVersion>()?;
+        let gradle = self.current_gradle_version()?;
+        if let Err(e) = self.check_toolchain(&gradle, &loom, minecraft) {
+            match self.pick_loom(&gradle, minecraft)? {
+                Some(loom_version) => {
+                    println!("{} Switching to loom {}.", e, loom_version);
+                    properties.set(&names.loom_version, &loom_version);
+                }
+                None => println!("WARNING: {} No known loom version works with gradle {} and Minecraft {}.", e, gradle, minecraft),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{app::App, common::*, properties::Properties, semantic_version::SemanticVersion, toolchain::parse_gradle_distribution_url};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Gradle,
+    Loom,
+    Loader,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 3] = [Tool::Gradle, Tool::Loom, Tool::Loader];
+}
+
+impl std::fmt::Display for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tool::Gradle => write!(f, "gradle"),
+            Tool::Loom => write!(f, "loom"),
+            Tool::Loader => write!(f, "loader"),
+        }
+    }
+}
+
+impl std::str::FromStr for Tool {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Tool::ALL.into_iter().find(|tool| tool.to_string() == s.to_lowercase()).ok_or(format!("Unknown tool '{}', expected gradle, loom or loader.", s).into())
+    }
+}
+
+/// Update of a tool to its latest version, worked out before changing anything so it can be previewed.
+#[derive(Debug, Clone)]
+pub struct PlannedUpdate {
+    pub tool: Tool,
+    pub current: String,
+    pub latest: String,
+    /// Value written to the project: the distribution url for gradle, the property value otherwise.
+    pub value: String,
+    /// Published SHA-256 of the gradle distribution, pinned in the wrapper properties.
+    pub checksum: Option<String>,
+    /// Why the update is known to break the build, according to the loom compatibility matrix.
+    pub blocked: Option<String>,
+}
+
+impl std::fmt::Display for PlannedUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} -> {}", self.tool, self.current, self.latest)?;
+        if let Some(reason) = &self.blocked { write!(f, " (blocked: {})", reason)? }
+        Ok(())
+    }
+}
+
+impl App {
+    fn read_wrapper_properties(&self) -> Result<Properties> {
+        Ok(Properties::parse(&std::fs::read_to_string(self.cwd.join(&self.config.paths.gradle_wrapper_properties))?))
+    }
+    
+    /// Work out the update of a tool to its latest version, `None` if it is up to date.
+    pub fn plan_update(&self, tool: Tool) -> Result<Option<PlannedUpdate>> {
+        let names = &self.config.properties;
+        let mut checksum = None;
+        let (current, latest, value, blocked) = match tool {
+            Tool::Gradle => {
+                let url = self.read_wrapper_properties()?.get("distributionUrl").ok_or("Could not find location of active gradle source.")?;
+                let latest = self.fetch_latest_gradle()?;
+                if url.trim() == latest.downloadUrl.trim() { return Ok(None) }
+                let current = parse_gradle_distribution_url(&url).map(|v| v.to_string()).unwrap_or_else(|| "?".to_owned());
+                let blocked = self.check_gradle_update(&latest.version.parse()?).err();
+                checksum = Some(self.api_request_text(&latest.checksumUrl)?);
+                (current, latest.version, latest.downloadUrl, blocked)
+            }
+            Tool::Loom => {
+                let properties = self.read_properties()?;
+                let current = self.find_property(&properties, &names.loom_version)?;
+                let latest = self.fetch_latest_loom()?;
+                let value = format!("{}-SNAPSHOT", latest);
+                if current.trim() == value { return Ok(None) }
+                let blocked = self.check_loom_update(&properties, &latest.parse::<SemanticVersion>()?).err();
+                (current, latest, value, blocked)
+            }
+            Tool::Loader => {
+                let current = self.find_property(&self.read_properties()?, &names.loader_version)?;
+                let latest = self.fetch_latest_loader()?;
+                if current.trim() == latest { return Ok(None) }
+                (current, latest.clone(), latest, None)
+            }
+        };
+        Ok(Some(PlannedUpdate { tool, current, latest, value, checksum, blocked: blocked.map(|e| e.to_string()) }))
+    }
+    
+    /// Plan the updates of several tools, reporting the tools that could not be checked.
+    pub fn plan_updates(&self, tools: &[Tool]) -> Vec<PlannedUpdate> {
+        let mut updates = vec![];
+        for &tool in tools {
+            match self.plan_update(tool) {
+                Ok(Some(update)) => updates.push(update),
+                Ok(None) => println!("{} is up to date.", tool),
+                Err(e) => println!("Could not check for {} updates: {}", tool, e),
+            }
+        }
+        updates
+    }
+    
+    pub fn apply_update(&self, update: &PlannedUpdate) -> Result<()> {
+        if let Some(reason) = &update.blocked {
+            return Err(format!("Not updating {} to {}: {}", update.tool, update.latest, reason).into())
+        }
+        let names = &self.config.properties;
+        match update.tool {
+            Tool::Gradle => {
+                let mut properties = self.read_wrapper_properties()?;
+                properties.set("distributionUrl", &update.value);
+                match &update.checksum {
+                    Some(checksum) => properties.set("distributionSha256Sum", checksum),
+                    None => { properties.remove("distributionSha256Sum"); }
+                }
+                write_atomic(self.cwd.join(&self.config.paths.gradle_wrapper_properties), properties.to_string())?;
+                println!("Updating gradle version to {}", update.latest);
+                self.run_gradle(["--version"])?;
+                match self.verify_wrapper_jar() {
+                    Ok(None) => (),
+                    Ok(Some(mismatch)) => println!("WARNING: {} Regenerate the wrapper with 'gradle wrapper'.", mismatch),
+                    Err(e) => println!("Could not verify the gradle wrapper jar: {}", e),
+                }
+            }
+            Tool::Loom | Tool::Loader => {
+                let mut properties = self.read_properties()?;
+                properties.set(if update.tool == Tool::Loom { &names.loom_version } else { &names.loader_version }, &update.value);
+                self.write_properties(&properties)?;
+                println!("Updating {} to version {}", update.tool, update.latest);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Apply every update that isn't blocked, reporting failures instead of stopping at the first one.
+    pub fn apply_updates(&self, updates: &[PlannedUpdate]) {
+        for update in updates {
+            if let Err(e) = self.apply_update(update) {
+                println!("{}", e);
+            }
+        }
+    }
+    
+    pub fn update_gradle(&self) -> Result<()> {
+        match self.plan_update(Tool::Gradle)? {
+            Some(update) => self.apply_update(&update),
+            None => {
+                println!("gradle is up to date.");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{app::App, common::*, config::Config, minecraft_version::MinecraftVersion};
+
+pub const WORKSPACE_FILE: &str = "ralli-workspace.toml";
+
+
+/// List of mod projects managed together, read from `ralli-workspace.toml`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Project folders, relative to the folder of the workspace file.
+    pub projects: Vec<PathBuf>,
+}
+
+impl Workspace {
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(WORKSPACE_FILE);
+        if !std::fs::exists(&path)? { return Err(format!("No {} in this folder.", WORKSPACE_FILE).into()) }
+        toml::from_str(&std::fs::read_to_string(&path)?).map_err(|e| format!("Invalid {}: {}", WORKSPACE_FILE, e).into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    /// The version could not be set up for testing, e.g. because a dependency has no build for it.
+    Unsupported(String),
+}
+
+/// Outcome for each tested Minecraft version, oldest first.
+pub type SweepResults = Vec<(MinecraftVersion, TestOutcome)>;
+
+impl std::fmt::Display for TestOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestOutcome::Passed => write!(f, "ok"),
+            TestOutcome::Failed => write!(f, "FAILED"),
+            TestOutcome::Unsupported(_) => write!(f, "unsupported"),
+        }
+    }
+}
+
+/// Print rows as columns padded to the widest cell.
+pub fn print_table(header: &[String], rows: &[Vec<String>]) {
+    let mut widths = header.iter().map(String::len).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let line = row.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect::<Vec<_>>().join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+impl App {
+    /// Run `f` for each project of the workspace in the working folder, with `cwd` and `config` switched to that project.
+    /// The HTTP client and the known Minecraft versions are shared between all projects.
+    pub(crate) fn for_each_project<T>(&mut self, mut f: impl FnMut(&Self) -> Result<T>) -> Result<Vec<(String, Result<T>)>> {
+        let workspace = Workspace::load(&self.cwd)?;
+        let root = self.cwd.clone();
+        let config = std::mem::take(&mut self.config);
+        
+        let mut results = vec![];
+        for project in &workspace.projects {
+            let name = project.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| project.display().to_string());
+            println!("== {} ==", name);
+            self.cwd = root.join(project);
+            let result = match Config::load(&self.cwd) {
+                Ok(config) => {
+                    self.config = config;
+                    f(self)
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result { println!("{}", e); }
+            results.push((name, result));
+        }
+        
+        self.cwd = root;
+        self.config = config;
+        Ok(results)
+    }
+    
+    /// Run a command in every project of the workspace: `status`, `deps`, `test <version>` or `sweep [<from> [<to>]]`.
+    /// These aren't recorded in the journal, since it only tracks the files of one project, so `undo` can't revert them.
+    pub fn run_workspace_command(&mut self, args: &[&str]) -> Result<()> {
+        match args {
+            ["status"] => {
+                self.for_each_project(App::status)?;
+            }
+            ["deps"] => {
+                let results = self.for_each_project(|app| { app.fetch_dependencies()?; app.save_current_state() })?;
+                let rows = results.into_iter().map(|(name, result)| vec![name, match result {
+                    Ok(()) => "ok".to_owned(),
+                    Err(e) => e.to_string(),
+                }]).collect::<Vec<_>>();
+                print_table(&["project".to_owned(), "dependencies".to_owned()], &rows);
+            }
+            ["test", version] => {
+                let index = self.version_index(version)?;
+                let results = self.for_each_project(|app| Ok(match app.test_version(index) {
+                    Ok(()) => {
+                        app.save_current_state()?;
+                        if app.run_gradle_status(["clean", "build"])? { TestOutcome::Passed } else { TestOutcome::Failed }
+                    }
+                    Err(e) => TestOutcome::Unsupported(e.to_string()),
+                }))?;
+                let version = self.mc_versions[index].0.clone();
+                self.print_compatibility_table(results.into_iter().map(|(name, result)| (name, result.map(|outcome| vec![(version.clone(), outcome)]))).collect());
+            }
+            ["sweep", bounds @ ..] if bounds.len() <= 2 => {
+                let results = self.for_each_project(|app| {
+                    let (oldest, newest) = app.sweep_bounds(bounds.first().copied(), bounds.get(1).copied())?;
+                    let results = app.sweep(oldest, newest)?;
+                    app.save_current_state()?;
+                    Ok(results)
+                })?;
+                self.print_compatibility_table(results);
+            }
+            _ => {
+                println!("Usage: workspace (status | deps | test <version> | sweep [<from> [<to>]])");
+                return Ok(())
+            }
+        }
+        if args != ["status"] {
+            println!("Workspace commands can't be undone. Use 'restore' in a project to go back to an earlier gradle.properties.");
+        }
+        Ok(())
+    }
+    
+    /// Print a table with a row for each tested Minecraft version and a column for each project.
+    fn print_compatibility_table(&self, results: Vec<(String, Result<SweepResults>)>) {
+        let mut header = vec!["minecraft".to_owned()];
+        header.extend(results.iter().map(|(name, _)| name.clone()));
+        
+        let tested = self.mc_versions.iter().map(|(version, _)| version).filter(|version| {
+            results.iter().any(|(_, result)| result.as_ref().is_ok_and(|outcomes| outcomes.iter().any(|(v, _)| v == *version)))
+        });
+        let rows = tested.map(|version| {
+            let mut row = vec![version.to_string()];
+            row.extend(results.iter().map(|(_, result)| match result {
+                Ok(outcomes) => outcomes.iter().find(|(v, _)| v == version).map(|(_, outcome)| outcome.to_string()).unwrap_or_else(|| "-".to_owned()),
+                Err(_) => "error".to_owned(),
+            }));
+            row
+        }).collect::<Vec<_>>();
+        print_table(&header, &rows);
+    }
+}
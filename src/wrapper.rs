@@ -0,0 +1,96 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::{api_structs::GradleVersion, app::App, common::*, properties::Properties, toolchain::gradle_distribution_version};
+
+/// Wrapper jar, next to the wrapper properties.
+pub const WRAPPER_JAR: &str = "gradle-wrapper.jar";
+
+
+/// Lowercase hex SHA-256 of some bytes, the format gradle publishes checksums in.
+pub fn sha256_hex(bytes: impl AsRef<[u8]>) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Describe how a wrapper jar differs from the official ones, given the published checksums by gradle version.
+/// `None` if it is the wrapper of any gradle version, since the jar isn't regenerated with every gradle update.
+pub fn wrapper_jar_mismatch(jar: &str, actual: &str, published: &BTreeMap<String, String>) -> Option<String> {
+    if published.values().any(|checksum| checksum == actual) { return None }
+    Some(format!("{} has checksum {}, which matches none of the {} published gradle wrappers.", jar, actual, published.len()))
+}
+
+impl App {
+    /// Published SHA-256 of a gradle distribution.
+    pub fn fetch_distribution_checksum(&self, distribution_url: &str) -> Result<String> {
+        self.api_request_text(&format!("{}.sha256", distribution_url))
+    }
+    
+    /// Published SHA-256 of the wrapper jar of a gradle version.
+    pub fn fetch_wrapper_checksum(&self, gradle_version: &str) -> Result<String> {
+        self.api_request_text(&format!("{}/gradle-{}-wrapper.jar.sha256", self.config.api.gradle_distributions, gradle_version))
+    }
+    
+    /// Write the published checksum of the current distribution to `distributionSha256Sum`, so the wrapper refuses a tampered download.
+    pub fn pin_distribution_checksum(&self) -> Result<()> {
+        let path = self.cwd.join(&self.config.paths.gradle_wrapper_properties);
+        let mut properties = Properties::parse(&std::fs::read_to_string(&path)?);
+        let url = properties.get("distributionUrl").ok_or("Could not find location of active gradle source.")?;
+        let checksum = self.fetch_distribution_checksum(&url)?;
+        if properties.get("distributionSha256Sum").is_some_and(|current| current == checksum) { return Ok(()) }
+        properties.set("distributionSha256Sum", &checksum);
+        write_atomic(&path, properties.to_string())?;
+        println!("Pinned the gradle distribution checksum {}.", checksum);
+        Ok(())
+    }
+    
+    /// Published wrapper jar checksums of every gradle release, by version.
+    /// Checksums already in the cache file aren't fetched again, since they never change.
+    pub fn fetch_wrapper_checksums(&self) -> Result<BTreeMap<String, String>> {
+        let cache_path = self.cwd.join(&self.config.paths.wrapper_checksum_cache);
+        let mut checksums = match std::fs::read_to_string(&cache_path) {
+            Ok(contents) => serde_json::from_str::<BTreeMap<String, String>>(&contents).unwrap_or_default(),
+            Err(_) => BTreeMap::new(),
+        };
+        let versions = self.api_request::<Vec<GradleVersion>>(&format!("{}/all", self.config.api.gradle_versions))?;
+        let mut result = Ok(());
+        // Snapshots and nightlies come and go, so only releases count
+        for version in versions.iter().filter(|version| !version.snapshot) {
+            let Some(url) = &version.wrapperChecksumUrl else { continue };
+            if checksums.contains_key(&version.version) { continue }
+            match self.api_request_text(url) {
+                Ok(checksum) => { checksums.insert(version.version.clone(), checksum); }
+                // Keep what was fetched so far, the rest is fetched next time
+                Err(e) => {
+                    result = Err(format!("Could not get the wrapper checksum of gradle {}: {}", version.version, e));
+                    break
+                }
+            }
+        }
+        
+        if let Some(parent) = cache_path.parent() { std::fs::create_dir_all(parent)?; }
+        write_atomic(&cache_path, serde_json::to_string_pretty(&checksums)? + "\n")?;
+        result?;
+        Ok(checksums)
+    }
+    
+    /// The wrapper jar, which sits next to the wrapper properties.
+    pub fn wrapper_jar_path(&self) -> PathBuf {
+        self.cwd.join(&self.config.paths.gradle_wrapper_properties).with_file_name(WRAPPER_JAR)
+    }
+    
+    /// Check the wrapper jar against the official wrapper jars of every gradle release.
+    /// Returns a description of the mismatch, if there is one.
+    pub fn verify_wrapper_jar(&self) -> Result<Option<String>> {
+        let properties_path = self.cwd.join(&self.config.paths.gradle_wrapper_properties);
+        let jar_path = self.wrapper_jar_path();
+        let actual = sha256_hex(std::fs::read(&jar_path)?);
+        
+        // Usually the jar is the one of the gradle version in use, which takes a single request to check
+        let url = Properties::parse(&std::fs::read_to_string(&properties_path)?).get("distributionUrl");
+        if let Some(version) = url.as_deref().and_then(gradle_distribution_version) && self.fetch_wrapper_checksum(version).is_ok_and(|expected| expected == actual) {
+            return Ok(None)
+        }
+        Ok(wrapper_jar_mismatch(&jar_path.display().to_string(), &actual, &self.fetch_wrapper_checksums()?))
+    }
+}
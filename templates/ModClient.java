@@ -0,0 +1,11 @@
+package {{package}}.client;
+
+import net.fabricmc.api.ClientModInitializer;
+
+public class {{class_name}}Client implements ClientModInitializer {
+	@Override
+	public void onInitializeClient() {
+		System.out.println("Client initialized!");
+		// This entrypoint is suitable for setting up client-specific logic, such as rendering.
+	}
+}
\ No newline at end of file